@@ -0,0 +1,858 @@
+use std::path::PathBuf;
+
+use anchor_lang::{AccountDeserialize, Discriminator};
+use anyhow::{anyhow, bail, Context, Result};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use solana_client::{
+    rpc_client::RpcClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+};
+use solana_options::{
+    instruction, PAUSE_ALL, PAUSE_BUY, PAUSE_EXERCISE, PAUSE_INITIALIZE, PAUSE_MARK, PAUSE_WRITE,
+};
+use solana_options_client::{
+    config_address, covered_call_address, covered_call_settlements, expiry_address,
+    fee_vault_address, instructions, market_address, Config, ConfigParams, CoveredCall,
+    ExerciseStyle, ExpiryData, MarketConfig, MarketParams, PriceKind, SettlementMode, ID,
+};
+use solana_sdk::{
+    account::Account,
+    commitment_config::CommitmentConfig,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{read_keypair_file, Keypair, Signer},
+    transaction::Transaction,
+};
+
+#[derive(Parser)]
+#[command(version, about = "Operate covered calls on the solana-options program")]
+struct Cli {
+    /// RPC endpoint
+    #[arg(short, long, global = true, default_value = "http://127.0.0.1:8899")]
+    url: String,
+    /// Keypair that signs and pays for transactions [default: ~/.config/solana/id.json]
+    #[arg(short, long, global = true)]
+    keypair: Option<PathBuf>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Write a covered call, depositing the base collateral
+    Initialize {
+        #[arg(long)]
+        mint_base: Pubkey,
+        #[arg(long)]
+        mint_quote: Pubkey,
+        /// Defaults to the base mint
+        #[arg(long)]
+        mint_premium: Option<Pubkey>,
+        /// Collateral in base units
+        #[arg(long)]
+        amount_base: u64,
+        /// Strike notional in quote units
+        #[arg(long)]
+        amount_quote: u64,
+        /// Expiry as a unix timestamp
+        #[arg(long)]
+        expiry: i64,
+        /// Asking premium in premium mint units
+        #[arg(long)]
+        premium: u64,
+        /// Defaults to the current unix timestamp
+        #[arg(long)]
+        nonce: Option<u64>,
+        #[arg(long, value_enum, default_value_t = Settlement::Cash)]
+        settlement: Settlement,
+        /// American options can be exercised before expiry at a live price
+        #[arg(long, value_enum, default_value_t = Style::European)]
+        style: Style,
+        /// Scheduled exercise date as a unix timestamp, repeat for each Bermudan date
+        #[arg(long = "exercise-date")]
+        exercise_dates: Vec<i64>,
+        /// Settle on the average of samples marked across the window instead of the last one
+        #[arg(long, value_enum, default_value_t = Price::Spot)]
+        price_kind: Price,
+        /// Leave unset to post an open offer
+        #[arg(long)]
+        buyer: Option<Pubkey>,
+    },
+    /// Buy a covered call, paying the premium
+    Buy {
+        address: Pubkey,
+        /// Defaults to the asking premium
+        #[arg(long)]
+        premium: Option<u64>,
+        /// Defaults to the signer
+        #[arg(long)]
+        buyer: Option<Pubkey>,
+    },
+    /// Record the oracle price for an expiry from a posted price update
+    Mark {
+        /// Base mint of a market on the feed to mark
+        #[arg(long)]
+        mint_base: Pubkey,
+        #[arg(long)]
+        mint_quote: Pubkey,
+        #[arg(long)]
+        expiry: i64,
+        /// PriceUpdateV2 account holding the price to record
+        #[arg(long)]
+        price_update: Pubkey,
+    },
+    /// Exercise a covered call as its buyer
+    Exercise {
+        address: Pubkey,
+        /// PriceUpdateV2 account to settle an American option before expiry
+        #[arg(long)]
+        price_update: Option<Pubkey>,
+        /// Scheduled date to settle a Bermudan option on
+        #[arg(long, conflicts_with = "price_update")]
+        date: Option<i64>,
+    },
+    /// Settle an expired in the money covered call for its buyer, earning a bounty
+    AutoExercise { address: Pubkey },
+    /// Close a covered call, returning collateral and rent to the seller
+    Close { address: Pubkey },
+    /// Assign the long side of a covered call to another wallet
+    TransferBuyer { address: Pubkey, new_buyer: Pubkey },
+    /// Assign the short side and its collateral claim to another wallet
+    TransferSeller { address: Pubkey, new_seller: Pubkey },
+    /// Close an expiry mark before it expires, returning rent to whoever paid for it
+    MarkClose {
+        #[arg(long, value_parser = parse_feed_id)]
+        feed_id: [u8; 32],
+        #[arg(long)]
+        expiry: i64,
+    },
+    /// Fix the average of an expiry's samples once its grace period has passed
+    MarkFinalize {
+        #[arg(long, value_parser = parse_feed_id)]
+        feed_id: [u8; 32],
+        #[arg(long)]
+        expiry: i64,
+    },
+    /// Create the protocol config as the upgrade authority, making the signer its admin
+    InitConfig {
+        #[command(flatten)]
+        params: ConfigArgs,
+    },
+    /// Change protocol settings as the admin
+    UpdateConfig {
+        #[command(flatten)]
+        params: ConfigArgs,
+    },
+    /// List a base and quote pair on a price feed as the admin, creating their fee vaults
+    InitMarket {
+        #[arg(long)]
+        mint_base: Pubkey,
+        #[arg(long)]
+        mint_quote: Pubkey,
+        /// Pyth feed id as hex, can't be changed later
+        #[arg(long, value_parser = parse_feed_id)]
+        feed_id: [u8; 32],
+        #[command(flatten)]
+        params: MarketArgs,
+    },
+    /// Change the limits of a listed pair as the admin
+    UpdateMarket {
+        #[arg(long)]
+        mint_base: Pubkey,
+        #[arg(long)]
+        mint_quote: Pubkey,
+        #[command(flatten)]
+        params: MarketArgs,
+    },
+    /// Hand the config to another admin
+    TransferAdmin { new_admin: Pubkey },
+    /// Stop the given instructions as the admin, or everything when none are given
+    Pause {
+        #[arg(value_enum)]
+        instructions: Vec<Pausable>,
+    },
+    /// Resume every paused instruction as the admin
+    Unpause,
+    /// Move collected protocol fees in a mint to a token account, as the admin
+    WithdrawFees {
+        mint: Pubkey,
+        destination: Pubkey,
+        amount: u64,
+    },
+    /// Decode and print a covered call, expiry mark, market or the config
+    Show { address: Pubkey },
+    /// List covered calls, optionally filtered by party
+    List {
+        #[arg(long)]
+        seller: Option<Pubkey>,
+        #[arg(long)]
+        buyer: Option<Pubkey>,
+        /// List expiry marks instead of covered calls
+        #[arg(long)]
+        expiries: bool,
+    },
+}
+
+// Unset flags keep the current value, or the default when initializing
+#[derive(Args)]
+struct ConfigArgs {
+    /// Seconds before expiry in which a price can be marked [default: 1800]
+    #[arg(long)]
+    mark_window: Option<i64>,
+    /// Widest oracle confidence interval accepted, in basis points of price [default: 100]
+    #[arg(long)]
+    max_conf_bps: Option<u16>,
+    /// Fee on premiums in basis points [default: 0]
+    #[arg(long)]
+    fee_premium_bps: Option<u16>,
+    /// Fee on in the money amounts in basis points [default: 0]
+    #[arg(long)]
+    fee_settlement_bps: Option<u16>,
+    /// Auto exercise bounty in basis points [default: 100]
+    #[arg(long)]
+    bounty_bps: Option<u16>,
+    /// Mint exempt from the extension check, repeat for each mint
+    #[arg(long = "allowed-mint")]
+    allowed_mints: Option<Vec<Pubkey>>,
+}
+
+impl ConfigArgs {
+    fn apply(self, params: ConfigParams) -> ConfigParams {
+        ConfigParams {
+            mark_window: self.mark_window.unwrap_or(params.mark_window),
+            max_conf_bps: self.max_conf_bps.unwrap_or(params.max_conf_bps),
+            fee_premium_bps: self.fee_premium_bps.unwrap_or(params.fee_premium_bps),
+            fee_settlement_bps: self.fee_settlement_bps.unwrap_or(params.fee_settlement_bps),
+            bounty_bps: self.bounty_bps.unwrap_or(params.bounty_bps),
+            allowed_mints: self.allowed_mints.unwrap_or(params.allowed_mints),
+        }
+    }
+}
+
+fn default_config_params() -> ConfigParams {
+    ConfigParams {
+        mark_window: 30 * 60,
+        max_conf_bps: 100,
+        fee_premium_bps: 0,
+        fee_settlement_bps: 0,
+        bounty_bps: 100,
+        allowed_mints: vec![],
+    }
+}
+
+// Unset flags keep the current value, or the default when listing
+#[derive(Args)]
+struct MarketArgs {
+    /// Shortest time from creation to expiry in seconds [default: 0]
+    #[arg(long)]
+    min_time_to_expiry: Option<i64>,
+    /// Longest time from creation to expiry in seconds [default: 31536000]
+    #[arg(long)]
+    max_time_to_expiry: Option<i64>,
+    /// Smallest collateral in base units [default: 1]
+    #[arg(long)]
+    min_amount_base: Option<u64>,
+}
+
+impl MarketArgs {
+    fn apply(self, params: MarketParams) -> MarketParams {
+        MarketParams {
+            min_time_to_expiry: self.min_time_to_expiry.unwrap_or(params.min_time_to_expiry),
+            max_time_to_expiry: self.max_time_to_expiry.unwrap_or(params.max_time_to_expiry),
+            min_amount_base: self.min_amount_base.unwrap_or(params.min_amount_base),
+        }
+    }
+}
+
+fn default_market_params() -> MarketParams {
+    MarketParams {
+        min_time_to_expiry: 0,
+        max_time_to_expiry: 365 * 24 * 60 * 60,
+        min_amount_base: 1,
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Settlement {
+    Cash,
+    Physical,
+}
+
+impl From<Settlement> for SettlementMode {
+    fn from(value: Settlement) -> Self {
+        match value {
+            Settlement::Cash => SettlementMode::Cash,
+            Settlement::Physical => SettlementMode::Physical,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Style {
+    European,
+    American,
+    Bermudan,
+}
+
+impl From<Style> for ExerciseStyle {
+    fn from(value: Style) -> Self {
+        match value {
+            Style::European => ExerciseStyle::European,
+            Style::American => ExerciseStyle::American,
+            Style::Bermudan => ExerciseStyle::Bermudan,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Price {
+    Spot,
+    Average,
+}
+
+impl From<Price> for PriceKind {
+    fn from(value: Price) -> Self {
+        match value {
+            Price::Spot => PriceKind::Spot,
+            Price::Average => PriceKind::Average,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Pausable {
+    Initialize,
+    Buy,
+    Write,
+    Mark,
+    Exercise,
+}
+
+impl Pausable {
+    fn flag(self) -> u32 {
+        match self {
+            Pausable::Initialize => PAUSE_INITIALIZE,
+            Pausable::Buy => PAUSE_BUY,
+            Pausable::Write => PAUSE_WRITE,
+            Pausable::Mark => PAUSE_MARK,
+            Pausable::Exercise => PAUSE_EXERCISE,
+        }
+    }
+}
+
+fn parse_feed_id(value: &str) -> Result<[u8; 32]> {
+    let value = value.trim_start_matches("0x");
+    if value.len() != 64 {
+        bail!("feed id must be 32 bytes of hex");
+    }
+    let mut feed_id = [0u8; 32];
+    for (i, byte) in feed_id.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&value[i * 2..i * 2 + 2], 16)?;
+    }
+    Ok(feed_id)
+}
+
+fn fmt_feed_id(feed_id: &[u8; 32]) -> String {
+    feed_id.iter().map(|x| format!("{x:02x}")).collect()
+}
+
+fn load_keypair(path: Option<PathBuf>) -> Result<Keypair> {
+    let path = match path {
+        Some(path) => path,
+        None => PathBuf::from(std::env::var("HOME")?).join(".config/solana/id.json"),
+    };
+    read_keypair_file(&path).map_err(|e| anyhow!("reading keypair {}: {e}", path.display()))
+}
+
+fn fetch<T: AccountDeserialize>(rpc: &RpcClient, address: &Pubkey) -> Result<T> {
+    let account = rpc
+        .get_account(address)
+        .with_context(|| format!("fetching {address}"))?;
+    Ok(T::try_deserialize(&mut &account.data[..])?)
+}
+
+fn fetch_expiry(rpc: &RpcClient, data: &CoveredCall) -> Result<Option<ExpiryData>> {
+    let (address, _) = expiry_address(&data.feed_id, data.timestamp_expiry);
+    let account = rpc
+        .get_account_with_commitment(&address, rpc.commitment())?
+        .value;
+    account
+        .map(|x| Ok(ExpiryData::try_deserialize(&mut &x.data[..])?))
+        .transpose()
+}
+
+// Program owning the mint, either spl-token or Token-2022
+fn fetch_token_program(rpc: &RpcClient, mint: &Pubkey) -> Result<Pubkey> {
+    let account = rpc
+        .get_account(mint)
+        .with_context(|| format!("fetching {mint}"))?;
+    Ok(account.owner)
+}
+
+fn send(rpc: &RpcClient, signer: &Keypair, ix: Instruction) -> Result<()> {
+    let blockhash = rpc.get_latest_blockhash()?;
+    let tx =
+        Transaction::new_signed_with_payer(&[ix], Some(&signer.pubkey()), &[signer], blockhash);
+    let signature = rpc.send_and_confirm_transaction(&tx)?;
+    println!("Signature: {signature}");
+    Ok(())
+}
+
+fn program_accounts(
+    rpc: &RpcClient,
+    filters: Vec<RpcFilterType>,
+) -> Result<Vec<(Pubkey, Account)>> {
+    Ok(rpc.get_program_accounts_with_config(
+        &ID,
+        RpcProgramAccountsConfig {
+            filters: Some(filters),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(solana_account_decoder::UiAccountEncoding::Base64),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    )?)
+}
+
+fn status(data: &CoveredCall, expiry: Option<&ExpiryData>, now: i64) -> &'static str {
+    if data.is_exercised {
+        "exercised"
+    } else if data.amount_premium.is_none() && now >= data.timestamp_expiry {
+        "expired unsold"
+    } else if data.amount_premium.is_none() && data.buyer == Pubkey::default() {
+        "open offer"
+    } else if data.amount_premium.is_none() {
+        "offered"
+    } else if now < data.timestamp_expiry {
+        "bought"
+    } else if expiry.is_some() {
+        "marked"
+    } else {
+        "awaiting mark"
+    }
+}
+
+fn print_covered_call(address: &Pubkey, data: &CoveredCall, expiry: Option<&ExpiryData>, now: i64) {
+    // Whole quote per whole base, independent of any oracle exponent
+    let strike = (data.amount_quote as f64 / 10f64.powi(data.decimals_quote.into()))
+        / (data.amount_base as f64 / 10f64.powi(data.decimals_base.into()));
+    println!("Covered call {address}");
+    println!("  status:      {}", status(data, expiry, now));
+    println!("  seller:      {}", data.seller);
+    println!("  creator:     {}", data.creator);
+    println!("  buyer:       {}", data.buyer);
+    println!("  base:        {} of {}", data.amount_base, data.mint_base);
+    println!(
+        "  quote:       {} of {}",
+        data.amount_quote, data.mint_quote
+    );
+    println!("  strike:      {strike}");
+    println!("  expiry:      {}", data.timestamp_expiry);
+    let settlement = match data.settlement {
+        SettlementMode::Cash => "cash",
+        SettlementMode::Physical => "physical",
+    };
+    println!("  settlement:  {settlement}");
+    let style = match data.style {
+        ExerciseStyle::European => "european",
+        ExerciseStyle::American => "american",
+        ExerciseStyle::Bermudan => "bermudan",
+    };
+    println!("  style:       {style}");
+    let price_kind = match data.price_kind {
+        PriceKind::Spot => "spot",
+        PriceKind::Average => "average",
+    };
+    println!("  price kind:  {price_kind}");
+    if !data.exercise_dates.is_empty() {
+        println!("  exercise on: {:?}", data.exercise_dates);
+    }
+    println!("  feed id:     {}", fmt_feed_id(&data.feed_id));
+    println!(
+        "  premium:     {} (ask {}) of {}",
+        data.amount_premium
+            .map_or("-".to_string(), |x| x.to_string()),
+        data.amount_premium_ask,
+        data.mint_premium
+    );
+    let marked = expiry.and_then(|x| Some((x, x.settlement_price(data.price_kind)?)));
+    if let Some((expiry, mark)) = marked {
+        let mark = mark as f64 * 10f64.powi(expiry.exponent);
+        println!("  mark:        {mark}");
+        match covered_call_settlements(data, expiry) {
+            Ok([seller, buyer]) => println!("  payoff:      seller {seller}, buyer {buyer}"),
+            Err(e) => println!("  payoff:      {e}"),
+        }
+    }
+}
+
+fn print_config(address: &Pubkey, config: &Config) {
+    println!("Config {address}");
+    println!("  admin:              {}", config.admin);
+    println!("  mark window:        {}s", config.params.mark_window);
+    println!("  max conf bps:       {}", config.params.max_conf_bps);
+    println!("  fee premium bps:    {}", config.params.fee_premium_bps);
+    println!("  fee settlement bps: {}", config.params.fee_settlement_bps);
+    println!("  bounty bps:         {}", config.params.bounty_bps);
+    for mint in &config.params.allowed_mints {
+        println!("  allowed mint:       {mint}");
+    }
+    println!("  paused:             {:#x}", config.paused);
+}
+
+fn print_market(address: &Pubkey, market: &MarketConfig) {
+    println!("Market {address}");
+    println!("  mint base:          {}", market.mint_base);
+    println!("  mint quote:         {}", market.mint_quote);
+    println!("  feed id:            {}", fmt_feed_id(&market.feed_id));
+    println!(
+        "  time to expiry:     {}s to {}s",
+        market.params.min_time_to_expiry, market.params.max_time_to_expiry
+    );
+    println!("  min amount base:    {}", market.params.min_amount_base);
+}
+
+fn print_expiry(address: &Pubkey, expiry: &ExpiryData) {
+    println!("Expiry {address}");
+    println!("  feed id:      {}", fmt_feed_id(&expiry.feed_id));
+    println!("  price:        {}e{}", expiry.price, expiry.exponent);
+    println!("  conf:         {}e{}", expiry.conf, expiry.exponent);
+    println!("  publish time: {}", expiry.publish_time);
+    println!("  payer:        {}", expiry.payer);
+    println!("  samples:      {}", expiry.sample_count);
+    if expiry.is_finalized {
+        println!(
+            "  average:      {}e{}",
+            expiry.average_price, expiry.exponent
+        );
+    }
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let rpc = RpcClient::new_with_commitment(cli.url, CommitmentConfig::confirmed());
+
+    match cli.command {
+        Command::Initialize {
+            mint_base,
+            mint_quote,
+            mint_premium,
+            amount_base,
+            amount_quote,
+            expiry,
+            premium,
+            nonce,
+            settlement,
+            style,
+            exercise_dates,
+            price_kind,
+            buyer,
+        } => {
+            let signer = load_keypair(cli.keypair)?;
+            let nonce = match nonce {
+                Some(nonce) => nonce,
+                None => std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)?
+                    .as_secs(),
+            };
+            let ix = instructions::initialize(
+                &signer.pubkey(),
+                buyer.as_ref(),
+                &mint_base,
+                &mint_quote,
+                &mint_premium.unwrap_or(mint_base),
+                instruction::Initialize {
+                    amount_base,
+                    amount_quote,
+                    timestamp_expiry: expiry,
+                    amount_premium_ask: premium,
+                    nonce,
+                    settlement: settlement.into(),
+                    style: style.into(),
+                    exercise_dates,
+                    price_kind: price_kind.into(),
+                },
+                &fetch_token_program(&rpc, &mint_base)?,
+                &fetch_token_program(&rpc, &mint_premium.unwrap_or(mint_base))?,
+            );
+            send(&rpc, &signer, ix)?;
+            println!(
+                "Covered call: {}",
+                covered_call_address(&signer.pubkey(), nonce).0
+            );
+        }
+        Command::Buy {
+            address,
+            premium,
+            buyer,
+        } => {
+            let signer = load_keypair(cli.keypair)?;
+            let data: CoveredCall = fetch(&rpc, &address)?;
+            let config: Config = fetch(&rpc, &config_address().0)?;
+            // The fee vault is only needed while a premium fee is charged
+            let fee_vault = (config.params.fee_premium_bps > 0)
+                .then(|| fee_vault_address(&data.mint_premium).0);
+            let ix = instructions::buy(
+                &signer.pubkey(),
+                &buyer.unwrap_or(signer.pubkey()),
+                &address,
+                &data,
+                premium.unwrap_or(data.amount_premium_ask),
+                fee_vault.as_ref(),
+                &fetch_token_program(&rpc, &data.mint_premium)?,
+            );
+            send(&rpc, &signer, ix)?;
+        }
+        Command::Mark {
+            mint_base,
+            mint_quote,
+            expiry,
+            price_update,
+        } => {
+            let signer = load_keypair(cli.keypair)?;
+            let market: MarketConfig = fetch(&rpc, &market_address(&mint_base, &mint_quote).0)?;
+            let ix = instructions::mark(&signer.pubkey(), &price_update, &market, expiry);
+            send(&rpc, &signer, ix)?;
+        }
+        Command::Exercise {
+            address,
+            price_update,
+            date,
+        } => {
+            let signer = load_keypair(cli.keypair)?;
+            let data: CoveredCall = fetch(&rpc, &address)?;
+            if data.buyer != signer.pubkey() {
+                bail!("only the buyer {} can exercise", data.buyer);
+            }
+            let token_program = fetch_token_program(&rpc, &data.mint_base)?;
+            let ix = match (data.settlement, price_update, date) {
+                (SettlementMode::Cash, Some(price_update), _) => {
+                    instructions::exercise_early(&address, &data, &price_update, &token_program)
+                }
+                (SettlementMode::Cash, None, Some(date)) => {
+                    instructions::exercise_scheduled(&address, &data, date, &token_program)
+                }
+                (SettlementMode::Cash, None, None) => {
+                    instructions::exercise(&address, &data, &token_program)
+                }
+                (SettlementMode::Physical, ..) => instructions::exercise_physical(
+                    &address,
+                    &data,
+                    &token_program,
+                    &fetch_token_program(&rpc, &data.mint_quote)?,
+                ),
+            };
+            send(&rpc, &signer, ix)?;
+        }
+        Command::AutoExercise { address } => {
+            let signer = load_keypair(cli.keypair)?;
+            let data: CoveredCall = fetch(&rpc, &address)?;
+            let ix = instructions::auto_exercise(
+                &signer.pubkey(),
+                &address,
+                &data,
+                &fetch_token_program(&rpc, &data.mint_base)?,
+            );
+            send(&rpc, &signer, ix)?;
+        }
+        Command::Close { address } => {
+            let signer = load_keypair(cli.keypair)?;
+            let data: CoveredCall = fetch(&rpc, &address)?;
+            let marked = fetch_expiry(&rpc, &data)?.is_some();
+            let ix = instructions::close(
+                &signer.pubkey(),
+                &address,
+                &data,
+                marked,
+                &fetch_token_program(&rpc, &data.mint_base)?,
+                &fetch_token_program(&rpc, &data.mint_quote)?,
+            );
+            send(&rpc, &signer, ix)?;
+        }
+        Command::TransferBuyer { address, new_buyer } => {
+            let signer = load_keypair(cli.keypair)?;
+            let data: CoveredCall = fetch(&rpc, &address)?;
+            if data.buyer != signer.pubkey() {
+                bail!("only the buyer {} can transfer", data.buyer);
+            }
+            let ix = instructions::transfer_buyer(&address, &data, &new_buyer);
+            send(&rpc, &signer, ix)?;
+        }
+        Command::TransferSeller {
+            address,
+            new_seller,
+        } => {
+            let signer = load_keypair(cli.keypair)?;
+            let data: CoveredCall = fetch(&rpc, &address)?;
+            if data.seller != signer.pubkey() {
+                bail!("only the seller {} can transfer", data.seller);
+            }
+            let ix = instructions::transfer_seller(&address, &data, &new_seller);
+            send(&rpc, &signer, ix)?;
+        }
+        Command::MarkClose { feed_id, expiry } => {
+            let signer = load_keypair(cli.keypair)?;
+            let ix = instructions::mark_close(&signer.pubkey(), feed_id, expiry);
+            send(&rpc, &signer, ix)?;
+        }
+        Command::MarkFinalize { feed_id, expiry } => {
+            let signer = load_keypair(cli.keypair)?;
+            let ix = instructions::mark_finalize(feed_id, expiry);
+            send(&rpc, &signer, ix)?;
+        }
+        Command::InitConfig { params } => {
+            let signer = load_keypair(cli.keypair)?;
+            let params = params.apply(default_config_params());
+            let ix = instructions::initialize_config(&signer.pubkey(), params);
+            send(&rpc, &signer, ix)?;
+            println!("Config: {}", config_address().0);
+        }
+        Command::UpdateConfig { params } => {
+            let signer = load_keypair(cli.keypair)?;
+            let config: Config = fetch(&rpc, &config_address().0)?;
+            if config.admin != signer.pubkey() {
+                bail!("only the admin {} can update the config", config.admin);
+            }
+            let ix = instructions::update_config(&signer.pubkey(), params.apply(config.params));
+            send(&rpc, &signer, ix)?;
+        }
+        Command::InitMarket {
+            mint_base,
+            mint_quote,
+            feed_id,
+            params,
+        } => {
+            let signer = load_keypair(cli.keypair)?;
+            let params = params.apply(default_market_params());
+            let ix = instructions::initialize_market(
+                &signer.pubkey(),
+                &mint_base,
+                &mint_quote,
+                feed_id,
+                params,
+                &fetch_token_program(&rpc, &mint_base)?,
+                &fetch_token_program(&rpc, &mint_quote)?,
+            );
+            send(&rpc, &signer, ix)?;
+            println!("Market: {}", market_address(&mint_base, &mint_quote).0);
+        }
+        Command::UpdateMarket {
+            mint_base,
+            mint_quote,
+            params,
+        } => {
+            let signer = load_keypair(cli.keypair)?;
+            let market: MarketConfig = fetch(&rpc, &market_address(&mint_base, &mint_quote).0)?;
+            let ix = instructions::update_market(
+                &signer.pubkey(),
+                &mint_base,
+                &mint_quote,
+                params.apply(market.params),
+            );
+            send(&rpc, &signer, ix)?;
+        }
+        Command::TransferAdmin { new_admin } => {
+            let signer = load_keypair(cli.keypair)?;
+            let ix = instructions::transfer_admin(&signer.pubkey(), &new_admin);
+            send(&rpc, &signer, ix)?;
+        }
+        Command::Pause {
+            instructions: flags,
+        } => {
+            let signer = load_keypair(cli.keypair)?;
+            let paused = if flags.is_empty() {
+                PAUSE_ALL
+            } else {
+                flags.iter().fold(0, |acc, x| acc | x.flag())
+            };
+            let ix = instructions::set_paused(&signer.pubkey(), paused);
+            send(&rpc, &signer, ix)?;
+        }
+        Command::Unpause => {
+            let signer = load_keypair(cli.keypair)?;
+            let ix = instructions::set_paused(&signer.pubkey(), 0);
+            send(&rpc, &signer, ix)?;
+        }
+        Command::WithdrawFees {
+            mint,
+            destination,
+            amount,
+        } => {
+            let signer = load_keypair(cli.keypair)?;
+            let ix = instructions::withdraw_fees(
+                &signer.pubkey(),
+                &mint,
+                &destination,
+                amount,
+                &fetch_token_program(&rpc, &mint)?,
+            );
+            send(&rpc, &signer, ix)?;
+        }
+        Command::Show { address } => {
+            let account = rpc
+                .get_account(&address)
+                .with_context(|| format!("fetching {address}"))?;
+            let now = rpc.get_block_time(rpc.get_slot()?)?;
+            if account.data.starts_with(&CoveredCall::DISCRIMINATOR) {
+                let data = CoveredCall::try_deserialize(&mut &account.data[..])?;
+                let expiry = fetch_expiry(&rpc, &data)?;
+                print_covered_call(&address, &data, expiry.as_ref(), now);
+            } else if account.data.starts_with(&ExpiryData::DISCRIMINATOR) {
+                let expiry = ExpiryData::try_deserialize(&mut &account.data[..])?;
+                print_expiry(&address, &expiry);
+            } else if account.data.starts_with(&Config::DISCRIMINATOR) {
+                let config = Config::try_deserialize(&mut &account.data[..])?;
+                print_config(&address, &config);
+            } else if account.data.starts_with(&MarketConfig::DISCRIMINATOR) {
+                let market = MarketConfig::try_deserialize(&mut &account.data[..])?;
+                print_market(&address, &market);
+            } else {
+                bail!("{address} is not a covered call, expiry, config or market account");
+            }
+        }
+        Command::List {
+            seller,
+            buyer,
+            expiries,
+        } => {
+            if expiries {
+                let filters = vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                    0,
+                    &ExpiryData::DISCRIMINATOR,
+                ))];
+                for (address, account) in program_accounts(&rpc, filters)? {
+                    let expiry = ExpiryData::try_deserialize(&mut &account.data[..])?;
+                    print_expiry(&address, &expiry);
+                }
+                return Ok(());
+            }
+            // Seller and buyer are the first two fields after the discriminator
+            let mut filters = vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                0,
+                &CoveredCall::DISCRIMINATOR,
+            ))];
+            if let Some(seller) = seller {
+                filters.push(RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                    8,
+                    seller.as_ref(),
+                )));
+            }
+            if let Some(buyer) = buyer {
+                filters.push(RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                    40,
+                    buyer.as_ref(),
+                )));
+            }
+            let now = rpc.get_block_time(rpc.get_slot()?)?;
+            for (address, account) in program_accounts(&rpc, filters)? {
+                let data = CoveredCall::try_deserialize(&mut &account.data[..])?;
+                let expiry = fetch_expiry(&rpc, &data)?;
+                print_covered_call(&address, &data, expiry.as_ref(), now);
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,810 @@
+use anchor_lang::{
+    prelude::*, solana_program::instruction::Instruction, system_program, InstructionData,
+};
+use anchor_spl::{
+    associated_token, associated_token::get_associated_token_address_with_program_id,
+};
+use solana_options::{accounts, instruction, ID};
+
+use crate::{
+    config_address, event_authority_address, expiry_address, fee_vault_address, market_address,
+    option_mint_address, program_data_address, writer_mint_address, CashSecuredPut, ConfigParams,
+    CoveredCall, MarketConfig, MarketParams, OptionSeries, SettlementMode,
+};
+
+// Builders take the token program owning each mint, either spl-token or Token-2022
+fn ata(wallet: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(wallet, mint, token_program)
+}
+
+fn build(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+// A `None` buyer posts an open offer that the first wallet to call `buy` takes
+#[allow(clippy::too_many_arguments)]
+pub fn initialize(
+    seller: &Pubkey,
+    buyer: Option<&Pubkey>,
+    mint_base: &Pubkey,
+    mint_quote: &Pubkey,
+    mint_premium: &Pubkey,
+    args: instruction::Initialize,
+    token_program: &Pubkey,
+    token_program_premium: &Pubkey,
+) -> Instruction {
+    let (data, _) = crate::covered_call_address(seller, args.nonce);
+    build(
+        accounts::Initialize {
+            seller: *seller,
+            buyer: buyer.copied(),
+            data,
+            mint_base: *mint_base,
+            mint_quote: *mint_quote,
+            mint_premium: *mint_premium,
+            ata_seller_base: ata(seller, mint_base, token_program),
+            ata_vault_base: ata(&data, mint_base, token_program),
+            config: config_address().0,
+            market: market_address(mint_base, mint_quote).0,
+            fee_vault_premium: fee_vault_address(mint_premium).0,
+            associated_token_program: associated_token::ID,
+            token_program: *token_program,
+            token_program_premium: *token_program_premium,
+            system_program: system_program::ID,
+            event_authority: event_authority_address().0,
+            program: ID,
+        },
+        args,
+    )
+}
+
+// The premium fee vault is created with the option, and only needed while a fee is charged
+pub fn buy(
+    payer: &Pubkey,
+    buyer: &Pubkey,
+    address: &Pubkey,
+    data: &CoveredCall,
+    amount_premium: u64,
+    fee_vault_premium: Option<&Pubkey>,
+    token_program: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::Buy {
+            payer: *payer,
+            seller: data.seller,
+            buyer: *buyer,
+            data: *address,
+            mint_premium: data.mint_premium,
+            ata_payer_premium: ata(payer, &data.mint_premium, token_program),
+            ata_seller_premium: ata(&data.seller, &data.mint_premium, token_program),
+            config: config_address().0,
+            fee_vault_premium: fee_vault_premium.copied(),
+            associated_token_program: associated_token::ID,
+            token_program: *token_program,
+            system_program: system_program::ID,
+            event_authority: event_authority_address().0,
+            program: ID,
+        },
+        instruction::Buy { amount_premium },
+    )
+}
+
+pub fn exercise(address: &Pubkey, data: &CoveredCall, token_program: &Pubkey) -> Instruction {
+    build(
+        accounts::Exercise {
+            buyer: data.buyer,
+            data: *address,
+            expiry: expiry_address(&data.feed_id, data.timestamp_expiry).0,
+            mint_base: data.mint_base,
+            mint_quote: data.mint_quote,
+            ata_buyer_base: ata(&data.buyer, &data.mint_base, token_program),
+            ata_vault_base: ata(address, &data.mint_base, token_program),
+            config: config_address().0,
+            fee_vault_base: Some(fee_vault_address(&data.mint_base).0),
+            associated_token_program: associated_token::ID,
+            token_program: *token_program,
+            system_program: system_program::ID,
+            event_authority: event_authority_address().0,
+            program: ID,
+        },
+        instruction::Exercise {},
+    )
+}
+
+// Settles an American option at the live price held by `price_update`
+pub fn exercise_early(
+    address: &Pubkey,
+    data: &CoveredCall,
+    price_update: &Pubkey,
+    token_program: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::ExerciseEarly {
+            buyer: data.buyer,
+            data: *address,
+            price_update: *price_update,
+            mint_base: data.mint_base,
+            ata_buyer_base: ata(&data.buyer, &data.mint_base, token_program),
+            ata_vault_base: ata(address, &data.mint_base, token_program),
+            config: config_address().0,
+            fee_vault_base: Some(fee_vault_address(&data.mint_base).0),
+            associated_token_program: associated_token::ID,
+            token_program: *token_program,
+            system_program: system_program::ID,
+            event_authority: event_authority_address().0,
+            program: ID,
+        },
+        instruction::ExerciseEarly {},
+    )
+}
+
+// Settles a Bermudan option on the mark of one of its scheduled dates
+pub fn exercise_scheduled(
+    address: &Pubkey,
+    data: &CoveredCall,
+    timestamp: i64,
+    token_program: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::ExerciseScheduled {
+            buyer: data.buyer,
+            data: *address,
+            expiry: expiry_address(&data.feed_id, timestamp).0,
+            mint_base: data.mint_base,
+            ata_buyer_base: ata(&data.buyer, &data.mint_base, token_program),
+            ata_vault_base: ata(address, &data.mint_base, token_program),
+            config: config_address().0,
+            fee_vault_base: Some(fee_vault_address(&data.mint_base).0),
+            associated_token_program: associated_token::ID,
+            token_program: *token_program,
+            system_program: system_program::ID,
+            event_authority: event_authority_address().0,
+            program: ID,
+        },
+        instruction::ExerciseScheduled { timestamp },
+    )
+}
+
+// Settles an expired in the money option on the buyer's behalf, paying `caller` a bounty
+pub fn auto_exercise(
+    caller: &Pubkey,
+    address: &Pubkey,
+    data: &CoveredCall,
+    token_program: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::AutoExercise {
+            caller: *caller,
+            buyer: data.buyer,
+            data: *address,
+            expiry: expiry_address(&data.feed_id, data.timestamp_expiry).0,
+            mint_base: data.mint_base,
+            ata_buyer_base: ata(&data.buyer, &data.mint_base, token_program),
+            ata_caller_base: ata(caller, &data.mint_base, token_program),
+            ata_vault_base: ata(address, &data.mint_base, token_program),
+            config: config_address().0,
+            fee_vault_base: Some(fee_vault_address(&data.mint_base).0),
+            associated_token_program: associated_token::ID,
+            token_program: *token_program,
+            system_program: system_program::ID,
+            event_authority: event_authority_address().0,
+            program: ID,
+        },
+        instruction::AutoExercise {},
+    )
+}
+
+pub fn exercise_physical(
+    address: &Pubkey,
+    data: &CoveredCall,
+    token_program: &Pubkey,
+    token_program_quote: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::ExercisePhysical {
+            buyer: data.buyer,
+            data: *address,
+            mint_base: data.mint_base,
+            mint_quote: data.mint_quote,
+            ata_buyer_base: ata(&data.buyer, &data.mint_base, token_program),
+            ata_buyer_quote: ata(&data.buyer, &data.mint_quote, token_program_quote),
+            ata_vault_base: ata(address, &data.mint_base, token_program),
+            ata_vault_quote: ata(address, &data.mint_quote, token_program_quote),
+            config: config_address().0,
+            associated_token_program: associated_token::ID,
+            token_program: *token_program,
+            token_program_quote: *token_program_quote,
+            system_program: system_program::ID,
+            event_authority: event_authority_address().0,
+            program: ID,
+        },
+        instruction::ExercisePhysical {},
+    )
+}
+
+// Pass `marked` once the expiry has a price so an out of the money option can close early
+pub fn close(
+    payer: &Pubkey,
+    address: &Pubkey,
+    data: &CoveredCall,
+    marked: bool,
+    token_program: &Pubkey,
+    token_program_quote: &Pubkey,
+) -> Instruction {
+    let physical = data.settlement == SettlementMode::Physical && data.is_exercised;
+    build(
+        accounts::Close {
+            payer: *payer,
+            seller: data.seller,
+            data: *address,
+            expiry: marked.then(|| expiry_address(&data.feed_id, data.timestamp_expiry).0),
+            mint_base: data.mint_base,
+            ata_seller_base: ata(&data.seller, &data.mint_base, token_program),
+            ata_vault_base: ata(address, &data.mint_base, token_program),
+            mint_quote: physical.then_some(data.mint_quote),
+            ata_seller_quote: physical
+                .then(|| ata(&data.seller, &data.mint_quote, token_program_quote)),
+            ata_vault_quote: physical.then(|| ata(address, &data.mint_quote, token_program_quote)),
+            associated_token_program: associated_token::ID,
+            token_program: *token_program,
+            token_program_quote: physical.then_some(*token_program_quote),
+            system_program: system_program::ID,
+            event_authority: event_authority_address().0,
+            program: ID,
+        },
+        instruction::Close {},
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn initialize_put(
+    seller: &Pubkey,
+    buyer: Option<&Pubkey>,
+    mint_base: &Pubkey,
+    mint_quote: &Pubkey,
+    mint_premium: &Pubkey,
+    args: instruction::InitializePut,
+    token_program: &Pubkey,
+    token_program_premium: &Pubkey,
+) -> Instruction {
+    let (data, _) = crate::cash_secured_put_address(seller, args.nonce);
+    build(
+        accounts::InitializePut {
+            seller: *seller,
+            buyer: buyer.copied(),
+            data,
+            mint_base: *mint_base,
+            mint_quote: *mint_quote,
+            mint_premium: *mint_premium,
+            ata_seller_quote: ata(seller, mint_quote, token_program),
+            ata_vault_quote: ata(&data, mint_quote, token_program),
+            config: config_address().0,
+            market: market_address(mint_base, mint_quote).0,
+            fee_vault_premium: fee_vault_address(mint_premium).0,
+            associated_token_program: associated_token::ID,
+            token_program: *token_program,
+            token_program_premium: *token_program_premium,
+            system_program: system_program::ID,
+            event_authority: event_authority_address().0,
+            program: ID,
+        },
+        args,
+    )
+}
+
+pub fn buy_put(
+    payer: &Pubkey,
+    buyer: &Pubkey,
+    address: &Pubkey,
+    data: &CashSecuredPut,
+    amount_premium: u64,
+    fee_vault_premium: Option<&Pubkey>,
+    token_program: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::BuyPut {
+            payer: *payer,
+            seller: data.seller,
+            buyer: *buyer,
+            data: *address,
+            mint_premium: data.mint_premium,
+            ata_payer_premium: ata(payer, &data.mint_premium, token_program),
+            ata_seller_premium: ata(&data.seller, &data.mint_premium, token_program),
+            config: config_address().0,
+            fee_vault_premium: fee_vault_premium.copied(),
+            associated_token_program: associated_token::ID,
+            token_program: *token_program,
+            system_program: system_program::ID,
+            event_authority: event_authority_address().0,
+            program: ID,
+        },
+        instruction::BuyPut { amount_premium },
+    )
+}
+
+pub fn exercise_put(
+    address: &Pubkey,
+    data: &CashSecuredPut,
+    token_program: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::ExercisePut {
+            buyer: data.buyer,
+            data: *address,
+            expiry: expiry_address(&data.feed_id, data.timestamp_expiry).0,
+            mint_base: data.mint_base,
+            mint_quote: data.mint_quote,
+            ata_buyer_quote: ata(&data.buyer, &data.mint_quote, token_program),
+            ata_vault_quote: ata(address, &data.mint_quote, token_program),
+            config: config_address().0,
+            fee_vault_quote: Some(fee_vault_address(&data.mint_quote).0),
+            associated_token_program: associated_token::ID,
+            token_program: *token_program,
+            system_program: system_program::ID,
+            event_authority: event_authority_address().0,
+            program: ID,
+        },
+        instruction::ExercisePut {},
+    )
+}
+
+// Settles an expired in the money put on the buyer's behalf, paying `caller` a bounty
+pub fn auto_exercise_put(
+    caller: &Pubkey,
+    address: &Pubkey,
+    data: &CashSecuredPut,
+    token_program: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::AutoExercisePut {
+            caller: *caller,
+            buyer: data.buyer,
+            data: *address,
+            expiry: expiry_address(&data.feed_id, data.timestamp_expiry).0,
+            mint_quote: data.mint_quote,
+            ata_buyer_quote: ata(&data.buyer, &data.mint_quote, token_program),
+            ata_caller_quote: ata(caller, &data.mint_quote, token_program),
+            ata_vault_quote: ata(address, &data.mint_quote, token_program),
+            config: config_address().0,
+            fee_vault_quote: Some(fee_vault_address(&data.mint_quote).0),
+            associated_token_program: associated_token::ID,
+            token_program: *token_program,
+            system_program: system_program::ID,
+            event_authority: event_authority_address().0,
+            program: ID,
+        },
+        instruction::AutoExercisePut {},
+    )
+}
+
+pub fn close_put(
+    payer: &Pubkey,
+    address: &Pubkey,
+    data: &CashSecuredPut,
+    marked: bool,
+    token_program: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::ClosePut {
+            payer: *payer,
+            seller: data.seller,
+            data: *address,
+            expiry: marked.then(|| expiry_address(&data.feed_id, data.timestamp_expiry).0),
+            mint_quote: data.mint_quote,
+            ata_seller_quote: ata(&data.seller, &data.mint_quote, token_program),
+            ata_vault_quote: ata(address, &data.mint_quote, token_program),
+            associated_token_program: associated_token::ID,
+            token_program: *token_program,
+            system_program: system_program::ID,
+            event_authority: event_authority_address().0,
+            program: ID,
+        },
+        instruction::ClosePut {},
+    )
+}
+
+pub fn mark(
+    payer: &Pubkey,
+    price_update: &Pubkey,
+    market: &MarketConfig,
+    timestamp_expiry: i64,
+) -> Instruction {
+    build(
+        accounts::Mark {
+            payer: *payer,
+            market: market_address(&market.mint_base, &market.mint_quote).0,
+            expiry: expiry_address(&market.feed_id, timestamp_expiry).0,
+            price_update: *price_update,
+            config: config_address().0,
+            system_program: system_program::ID,
+            event_authority: event_authority_address().0,
+            program: ID,
+        },
+        instruction::Mark { timestamp_expiry },
+    )
+}
+
+pub fn mark_close(payer: &Pubkey, feed_id: [u8; 32], timestamp_expiry: i64) -> Instruction {
+    build(
+        accounts::MarkClose {
+            payer: *payer,
+            expiry: expiry_address(&feed_id, timestamp_expiry).0,
+            system_program: system_program::ID,
+            event_authority: event_authority_address().0,
+            program: ID,
+        },
+        instruction::MarkClose {
+            timestamp_expiry,
+            feed_id,
+        },
+    )
+}
+
+pub fn mark_finalize(feed_id: [u8; 32], timestamp_expiry: i64) -> Instruction {
+    build(
+        accounts::MarkFinalize {
+            expiry: expiry_address(&feed_id, timestamp_expiry).0,
+            config: config_address().0,
+            event_authority: event_authority_address().0,
+            program: ID,
+        },
+        instruction::MarkFinalize {
+            timestamp_expiry,
+            feed_id,
+        },
+    )
+}
+
+pub fn transfer_buyer(address: &Pubkey, data: &CoveredCall, new_buyer: &Pubkey) -> Instruction {
+    build(
+        accounts::TransferBuyer {
+            buyer: data.buyer,
+            new_buyer: *new_buyer,
+            data: *address,
+            event_authority: event_authority_address().0,
+            program: ID,
+        },
+        instruction::TransferBuyer {},
+    )
+}
+
+pub fn transfer_buyer_put(
+    address: &Pubkey,
+    data: &CashSecuredPut,
+    new_buyer: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::TransferBuyerPut {
+            buyer: data.buyer,
+            new_buyer: *new_buyer,
+            data: *address,
+            event_authority: event_authority_address().0,
+            program: ID,
+        },
+        instruction::TransferBuyerPut {},
+    )
+}
+
+pub fn transfer_seller(address: &Pubkey, data: &CoveredCall, new_seller: &Pubkey) -> Instruction {
+    build(
+        accounts::TransferSeller {
+            seller: data.seller,
+            new_seller: *new_seller,
+            data: *address,
+            event_authority: event_authority_address().0,
+            program: ID,
+        },
+        instruction::TransferSeller {},
+    )
+}
+
+pub fn transfer_seller_put(
+    address: &Pubkey,
+    data: &CashSecuredPut,
+    new_seller: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::TransferSellerPut {
+            seller: data.seller,
+            new_seller: *new_seller,
+            data: *address,
+            event_authority: event_authority_address().0,
+            program: ID,
+        },
+        instruction::TransferSellerPut {},
+    )
+}
+
+// `feed_id` must be the one of the pair's market, it is part of the series address
+pub fn initialize_series(
+    payer: &Pubkey,
+    mint_base: &Pubkey,
+    mint_quote: &Pubkey,
+    feed_id: &[u8; 32],
+    args: instruction::InitializeSeries,
+    token_program: &Pubkey,
+) -> Instruction {
+    let (series, _) = crate::option_series_address(
+        mint_base,
+        mint_quote,
+        feed_id,
+        args.timestamp_expiry,
+        args.amount_base,
+        args.amount_quote,
+        args.price_kind,
+    );
+    build(
+        accounts::InitializeSeries {
+            payer: *payer,
+            market: market_address(mint_base, mint_quote).0,
+            series,
+            mint_base: *mint_base,
+            mint_quote: *mint_quote,
+            mint_option: option_mint_address(&series).0,
+            mint_writer: writer_mint_address(&series).0,
+            ata_vault_base: ata(&series, mint_base, token_program),
+            config: config_address().0,
+            associated_token_program: associated_token::ID,
+            token_program: *token_program,
+            system_program: system_program::ID,
+            event_authority: event_authority_address().0,
+            program: ID,
+        },
+        args,
+    )
+}
+
+pub fn write(
+    seller: &Pubkey,
+    address: &Pubkey,
+    series: &OptionSeries,
+    amount: u64,
+    token_program: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::Write {
+            seller: *seller,
+            series: *address,
+            mint_base: series.mint_base,
+            mint_option: series.mint_option,
+            mint_writer: series.mint_writer,
+            ata_seller_base: ata(seller, &series.mint_base, token_program),
+            ata_seller_option: ata(seller, &series.mint_option, token_program),
+            ata_seller_writer: ata(seller, &series.mint_writer, token_program),
+            ata_vault_base: ata(address, &series.mint_base, token_program),
+            config: config_address().0,
+            market: market_address(&series.mint_base, &series.mint_quote).0,
+            associated_token_program: associated_token::ID,
+            token_program: *token_program,
+            system_program: system_program::ID,
+            event_authority: event_authority_address().0,
+            program: ID,
+        },
+        instruction::Write { amount },
+    )
+}
+
+pub fn exercise_series(
+    buyer: &Pubkey,
+    address: &Pubkey,
+    series: &OptionSeries,
+    amount: u64,
+    token_program: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::ExerciseSeries {
+            buyer: *buyer,
+            series: *address,
+            expiry: expiry_address(&series.feed_id, series.timestamp_expiry).0,
+            mint_base: series.mint_base,
+            mint_option: series.mint_option,
+            ata_buyer_option: ata(buyer, &series.mint_option, token_program),
+            ata_buyer_base: ata(buyer, &series.mint_base, token_program),
+            ata_vault_base: ata(address, &series.mint_base, token_program),
+            config: config_address().0,
+            fee_vault_base: Some(fee_vault_address(&series.mint_base).0),
+            associated_token_program: associated_token::ID,
+            token_program: *token_program,
+            system_program: system_program::ID,
+            event_authority: event_authority_address().0,
+            program: ID,
+        },
+        instruction::ExerciseSeries { amount },
+    )
+}
+
+pub fn redeem(
+    seller: &Pubkey,
+    address: &Pubkey,
+    series: &OptionSeries,
+    amount: u64,
+    token_program: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::Redeem {
+            seller: *seller,
+            series: *address,
+            expiry: expiry_address(&series.feed_id, series.timestamp_expiry).0,
+            mint_base: series.mint_base,
+            mint_writer: series.mint_writer,
+            ata_seller_writer: ata(seller, &series.mint_writer, token_program),
+            ata_seller_base: ata(seller, &series.mint_base, token_program),
+            ata_vault_base: ata(address, &series.mint_base, token_program),
+            associated_token_program: associated_token::ID,
+            token_program: *token_program,
+            system_program: system_program::ID,
+            event_authority: event_authority_address().0,
+            program: ID,
+        },
+        instruction::Redeem { amount },
+    )
+}
+
+// Must be signed by the program's upgrade authority, which becomes the admin
+pub fn initialize_config(admin: &Pubkey, params: ConfigParams) -> Instruction {
+    build(
+        accounts::InitializeConfig {
+            admin: *admin,
+            program_data: program_data_address().0,
+            config: config_address().0,
+            system_program: system_program::ID,
+            event_authority: event_authority_address().0,
+            program: ID,
+        },
+        instruction::InitializeConfig { params },
+    )
+}
+
+pub fn update_config(admin: &Pubkey, params: ConfigParams) -> Instruction {
+    build(
+        accounts::UpdateConfig {
+            admin: *admin,
+            config: config_address().0,
+            event_authority: event_authority_address().0,
+            program: ID,
+        },
+        instruction::UpdateConfig { params },
+    )
+}
+
+// Lists a pair, binding it to `feed_id` for good
+pub fn initialize_market(
+    admin: &Pubkey,
+    mint_base: &Pubkey,
+    mint_quote: &Pubkey,
+    feed_id: [u8; 32],
+    params: MarketParams,
+    token_program: &Pubkey,
+    token_program_quote: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::InitializeMarket {
+            admin: *admin,
+            config: config_address().0,
+            mint_base: *mint_base,
+            mint_quote: *mint_quote,
+            market: market_address(mint_base, mint_quote).0,
+            fee_vault_base: fee_vault_address(mint_base).0,
+            fee_vault_quote: fee_vault_address(mint_quote).0,
+            token_program: *token_program,
+            token_program_quote: *token_program_quote,
+            system_program: system_program::ID,
+            event_authority: event_authority_address().0,
+            program: ID,
+        },
+        instruction::InitializeMarket { feed_id, params },
+    )
+}
+
+pub fn update_market(
+    admin: &Pubkey,
+    mint_base: &Pubkey,
+    mint_quote: &Pubkey,
+    params: MarketParams,
+) -> Instruction {
+    build(
+        accounts::UpdateMarket {
+            admin: *admin,
+            config: config_address().0,
+            market: market_address(mint_base, mint_quote).0,
+            event_authority: event_authority_address().0,
+            program: ID,
+        },
+        instruction::UpdateMarket { params },
+    )
+}
+
+// Replaces the pause mask, see the PAUSE_* constants
+pub fn set_paused(admin: &Pubkey, paused: u32) -> Instruction {
+    build(
+        accounts::SetPaused {
+            admin: *admin,
+            config: config_address().0,
+            event_authority: event_authority_address().0,
+            program: ID,
+        },
+        instruction::SetPaused { paused },
+    )
+}
+
+pub fn transfer_admin(admin: &Pubkey, new_admin: &Pubkey) -> Instruction {
+    build(
+        accounts::TransferAdmin {
+            admin: *admin,
+            new_admin: *new_admin,
+            config: config_address().0,
+            event_authority: event_authority_address().0,
+            program: ID,
+        },
+        instruction::TransferAdmin {},
+    )
+}
+
+// Sends `amount` of the fees collected in `mint` to the `destination` token account
+pub fn withdraw_fees(
+    admin: &Pubkey,
+    mint: &Pubkey,
+    destination: &Pubkey,
+    amount: u64,
+    token_program: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::WithdrawFees {
+            admin: *admin,
+            config: config_address().0,
+            mint: *mint,
+            fee_vault: fee_vault_address(mint).0,
+            destination: *destination,
+            token_program: *token_program,
+            event_authority: event_authority_address().0,
+            program: ID,
+        },
+        instruction::WithdrawFees { amount },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::prelude::*;
+    use anchor_spl::token;
+    use solana_options::{instruction, ExerciseStyle, PriceKind, SettlementMode, ID};
+
+    use crate::{covered_call_address, instructions};
+
+    #[test]
+    fn test_initialize_open_offer() {
+        let seller = Pubkey::new_unique();
+        let mint_base = Pubkey::new_unique();
+        let mint_quote = Pubkey::new_unique();
+        let ix = instructions::initialize(
+            &seller,
+            None,
+            &mint_base,
+            &mint_quote,
+            &mint_base,
+            instruction::Initialize {
+                amount_base: 1_000_000_000,
+                amount_quote: 130_000_000,
+                timestamp_expiry: 1_700_000_000,
+                amount_premium_ask: 10,
+                nonce: 7,
+                settlement: SettlementMode::Cash,
+                style: ExerciseStyle::European,
+                exercise_dates: vec![],
+                price_kind: PriceKind::Spot,
+            },
+            &token::ID,
+            &token::ID,
+        );
+        assert_eq!(ix.program_id, ID);
+        assert_eq!(ix.accounts[0].pubkey, seller);
+        assert!(ix.accounts[0].is_signer);
+        // Anchor marks a missing optional account with the program id
+        assert_eq!(ix.accounts[1].pubkey, ID);
+        assert_eq!(ix.accounts[2].pubkey, covered_call_address(&seller, 7).0);
+    }
+}
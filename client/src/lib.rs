@@ -0,0 +1,10 @@
+pub mod instructions;
+pub mod pda;
+pub mod state;
+pub mod strike;
+
+pub use pda::*;
+pub use state::*;
+pub use strike::*;
+
+pub use solana_options::ID;
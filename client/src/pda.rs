@@ -0,0 +1,185 @@
+use anchor_lang::{prelude::*, solana_program::bpf_loader_upgradeable};
+use solana_options::ID;
+
+use crate::PriceKind;
+
+// Options are keyed by the seller that initialized them, which survives transfer_seller
+
+pub fn covered_call_address(creator: &Pubkey, nonce: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            "covered-call".as_bytes(),
+            creator.as_ref(),
+            &nonce.to_le_bytes(),
+        ],
+        &ID,
+    )
+}
+
+pub fn cash_secured_put_address(creator: &Pubkey, nonce: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            "cash-secured-put".as_bytes(),
+            creator.as_ref(),
+            &nonce.to_le_bytes(),
+        ],
+        &ID,
+    )
+}
+
+pub fn expiry_address(feed_id: &[u8; 32], timestamp_expiry: i64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            "expiry-meta".as_bytes(),
+            feed_id,
+            &timestamp_expiry.to_le_bytes(),
+        ],
+        &ID,
+    )
+}
+
+// Signs the self-CPI that carries emitted events
+pub fn event_authority_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"__event_authority"], &ID)
+}
+
+pub fn option_series_address(
+    mint_base: &Pubkey,
+    mint_quote: &Pubkey,
+    feed_id: &[u8; 32],
+    timestamp_expiry: i64,
+    amount_base: u64,
+    amount_quote: u64,
+    price_kind: PriceKind,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            "option-series".as_bytes(),
+            mint_base.as_ref(),
+            mint_quote.as_ref(),
+            feed_id,
+            &timestamp_expiry.to_le_bytes(),
+            &amount_base.to_le_bytes(),
+            &amount_quote.to_le_bytes(),
+            &[price_kind as u8],
+        ],
+        &ID,
+    )
+}
+
+pub fn option_mint_address(series: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&["option-mint".as_bytes(), series.as_ref()], &ID)
+}
+
+pub fn writer_mint_address(series: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&["writer-mint".as_bytes(), series.as_ref()], &ID)
+}
+
+pub fn config_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&["config".as_bytes()], &ID)
+}
+
+pub fn market_address(mint_base: &Pubkey, mint_quote: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &["market".as_bytes(), mint_base.as_ref(), mint_quote.as_ref()],
+        &ID,
+    )
+}
+
+// Protocol fees accumulate here per mint, owned by the config PDA
+pub fn fee_vault_address(mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&["fee-vault".as_bytes(), mint.as_ref()], &ID)
+}
+
+// Holds the upgrade authority, which is the only key allowed to initialize the config
+pub fn program_data_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ID.as_ref()], &bpf_loader_upgradeable::ID)
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::prelude::*;
+    use solana_options::PriceKind;
+
+    use crate::pda::*;
+
+    // Expected addresses are fixed, so changing a seed here or in the program breaks these
+    const CREATOR: Pubkey = Pubkey::new_from_array([1; 32]);
+    const MINT_BASE: Pubkey = Pubkey::new_from_array([2; 32]);
+    const MINT_QUOTE: Pubkey = Pubkey::new_from_array([3; 32]);
+    const FEED_ID: [u8; 32] = [4; 32];
+
+    fn series(price_kind: PriceKind) -> Pubkey {
+        option_series_address(
+            &MINT_BASE,
+            &MINT_QUOTE,
+            &FEED_ID,
+            1_700_000_000,
+            1_000_000_000,
+            130_000_000,
+            price_kind,
+        )
+        .0
+    }
+
+    #[test]
+    fn test_option_addresses() {
+        assert_eq!(
+            covered_call_address(&CREATOR, 7).0.to_string(),
+            "5CXg7LAdf3ZtjteXWUJR33kevJvYUjoyeVGZz9FCKmyy"
+        );
+        assert_eq!(
+            cash_secured_put_address(&CREATOR, 7).0.to_string(),
+            "EpGGkRvYRWskmx932U8KFB2CF2nLtFDbS9cnerAh4MT2"
+        );
+    }
+
+    #[test]
+    fn test_program_addresses() {
+        assert_eq!(
+            config_address().0.to_string(),
+            "HqLHNBdCrUpo9nqi563SrdkrCDg9BHnzETtta7ScTMkb"
+        );
+        assert_eq!(
+            market_address(&MINT_BASE, &MINT_QUOTE).0.to_string(),
+            "3a8Q1dmcWNjujCUXDP9jTXe7mSGntwaEYFs7F6yJ5D86"
+        );
+        assert_eq!(
+            expiry_address(&FEED_ID, 1_700_000_000).0.to_string(),
+            "FNZ4ZSiztvbRzjF8piwizPFxLxpCco1TBiUMuMxEPuSi"
+        );
+        assert_eq!(
+            fee_vault_address(&MINT_BASE).0.to_string(),
+            "HzRP9VEXWnyXPTasTrVBnre27HiMVkczZYmi2qj6vXwF"
+        );
+        assert_eq!(
+            event_authority_address().0.to_string(),
+            "2V14Ggc3nYHs9noa3xeWz8ifsDyA1CBx8goMkVtpSNQE"
+        );
+        assert_eq!(
+            program_data_address().0.to_string(),
+            "DMDpsqoNWZAcRv8FqPyM5dTr29gNw6h62LffB8qW9Tah"
+        );
+    }
+
+    #[test]
+    fn test_option_series_addresses() {
+        assert_eq!(
+            series(PriceKind::Spot).to_string(),
+            "8AeznBKwGrF6DzjUMNosv9QBpqBhUkwdMN9fYn7xYbZ8"
+        );
+        // Same terms priced differently are a different series
+        assert_eq!(
+            series(PriceKind::Average).to_string(),
+            "4mBTgj1gkMy1Upn1fdJTFMnJyWDf6kThSJKhZF8Kgqex"
+        );
+        assert_eq!(
+            option_mint_address(&series(PriceKind::Spot)).0.to_string(),
+            "CdpbL442dZqFc4gm57fKtm38FTQxqJ34HHgzWiJaoBx5"
+        );
+        assert_eq!(
+            writer_mint_address(&series(PriceKind::Spot)).0.to_string(),
+            "EkRAQTLBnrY5MjiZnrm3gf7YUK9srGLTuNB7PcwCPX9g"
+        );
+    }
+}
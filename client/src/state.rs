@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+
+pub use solana_options::{
+    CashSecuredPut, Config, ConfigParams, CoveredCall, ExerciseStyle, ExpiryData, MarketConfig,
+    MarketParams, OptionSeries, PriceKind, SettlementMode,
+};
+
+// Decodes any program account, checking its discriminator
+pub fn decode<T: AccountDeserialize>(data: &[u8]) -> Result<T> {
+    T::try_deserialize(&mut &data[..])
+}
+
+pub fn decode_covered_call(data: &[u8]) -> Result<CoveredCall> {
+    decode(data)
+}
+
+pub fn decode_cash_secured_put(data: &[u8]) -> Result<CashSecuredPut> {
+    decode(data)
+}
+
+pub fn decode_expiry(data: &[u8]) -> Result<ExpiryData> {
+    decode(data)
+}
+
+pub fn decode_option_series(data: &[u8]) -> Result<OptionSeries> {
+    decode(data)
+}
+
+pub fn decode_config(data: &[u8]) -> Result<Config> {
+    decode(data)
+}
+
+pub fn decode_market(data: &[u8]) -> Result<MarketConfig> {
+    decode(data)
+}
@@ -0,0 +1,210 @@
+use anchor_lang::prelude::*;
+
+use solana_options::error::ErrorCode;
+pub use solana_options::math::{
+    calc_strike, get_buyer_settlement, get_put_settlements, get_settlements,
+};
+
+use crate::{CashSecuredPut, CoveredCall, ExpiryData, OptionSeries};
+
+pub fn covered_call_strike(data: &CoveredCall, exponent: i32) -> Result<i64> {
+    calc_strike(
+        data.amount_base,
+        data.amount_quote,
+        data.decimals_base,
+        data.decimals_quote,
+        exponent,
+    )
+}
+
+pub fn cash_secured_put_strike(data: &CashSecuredPut, exponent: i32) -> Result<i64> {
+    calc_strike(
+        data.amount_base,
+        data.amount_quote,
+        data.decimals_base,
+        data.decimals_quote,
+        exponent,
+    )
+}
+
+pub fn option_series_strike(series: &OptionSeries, exponent: i32) -> Result<i64> {
+    calc_strike(
+        series.amount_base,
+        series.amount_quote,
+        series.decimals_base,
+        series.decimals_quote,
+        exponent,
+    )
+}
+
+// [seller, buyer] split of the base vault once the expiry is marked, or finalized if averaged
+pub fn covered_call_settlements(data: &CoveredCall, expiry: &ExpiryData) -> Result<[u64; 2]> {
+    let mark = expiry
+        .settlement_price(data.price_kind)
+        .ok_or(ErrorCode::OptionNotMarked)?;
+    let strike = covered_call_strike(data, expiry.exponent)?;
+    get_settlements(strike, mark, data.amount_base)
+}
+
+// [seller, buyer] split of the quote vault once the expiry is marked
+pub fn cash_secured_put_settlements(
+    data: &CashSecuredPut,
+    expiry: &ExpiryData,
+) -> Result<[u64; 2]> {
+    let strike = cash_secured_put_strike(data, expiry.exponent)?;
+    get_put_settlements(strike, expiry.price, data.amount_quote)
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::prelude::*;
+    use solana_options::error::ErrorCode;
+
+    use crate::strike::*;
+    use crate::{ExerciseStyle, PriceKind, SettlementMode};
+
+    // 1 SOL for 130 USDC, a strike of 130 with pyth's -8 exponent
+    fn covered_call(price_kind: PriceKind) -> CoveredCall {
+        CoveredCall {
+            seller: Pubkey::new_unique(),
+            buyer: Pubkey::new_unique(),
+            amount_base: 1_000_000_000,
+            amount_quote: 130_000_000,
+            timestamp_expiry: 1_700_000_000,
+            mint_quote: Pubkey::new_unique(),
+            mint_base: Pubkey::new_unique(),
+            bump: 255,
+            amount_premium: Some(10),
+            is_exercised: false,
+            timestamp_created: 1_699_000_000,
+            amount_premium_ask: 10,
+            mint_premium: Pubkey::new_unique(),
+            nonce: 0,
+            settlement: SettlementMode::Cash,
+            feed_id: [0; 32],
+            decimals_base: 9,
+            decimals_quote: 6,
+            creator: Pubkey::new_unique(),
+            style: ExerciseStyle::European,
+            exercise_dates: vec![],
+            price_kind,
+        }
+    }
+
+    fn cash_secured_put() -> CashSecuredPut {
+        CashSecuredPut {
+            seller: Pubkey::new_unique(),
+            buyer: Pubkey::new_unique(),
+            amount_base: 1_000_000_000,
+            amount_quote: 130_000_000,
+            timestamp_expiry: 1_700_000_000,
+            mint_quote: Pubkey::new_unique(),
+            mint_base: Pubkey::new_unique(),
+            bump: 255,
+            amount_premium: Some(10),
+            is_exercised: false,
+            timestamp_created: 1_699_000_000,
+            amount_premium_ask: 10,
+            mint_premium: Pubkey::new_unique(),
+            nonce: 0,
+            feed_id: [0; 32],
+            decimals_base: 9,
+            decimals_quote: 6,
+            creator: Pubkey::new_unique(),
+        }
+    }
+
+    fn expiry(price: i64) -> ExpiryData {
+        ExpiryData {
+            price,
+            conf: 0,
+            exponent: -8,
+            publish_time: 1_700_000_000,
+            bump: 255,
+            payer: Pubkey::new_unique(),
+            feed_id: [0; 32],
+            price_sum: 0,
+            sample_count: 0,
+            sample_buckets: 0,
+            average_price: 0,
+            is_finalized: false,
+        }
+    }
+
+    #[test]
+    fn test_strikes() {
+        let data = covered_call(PriceKind::Spot);
+        assert_eq!(covered_call_strike(&data, -8).unwrap(), 130_0000_0000);
+        assert_eq!(covered_call_strike(&data, -5).unwrap(), 130_00000);
+        assert_eq!(
+            cash_secured_put_strike(&cash_secured_put(), -8).unwrap(),
+            130_0000_0000
+        );
+        let series = OptionSeries {
+            mint_base: data.mint_base,
+            mint_quote: data.mint_quote,
+            amount_base: 100_000_000_000,
+            amount_quote: 20_000_000,
+            timestamp_expiry: data.timestamp_expiry,
+            feed_id: data.feed_id,
+            decimals_base: 5,
+            decimals_quote: 6,
+            mint_option: Pubkey::new_unique(),
+            mint_writer: Pubkey::new_unique(),
+            bump: 255,
+            price_kind: PriceKind::Spot,
+        };
+        // 1M BONK for 20 USDC, pyth quotes BONK with 10 decimals
+        assert_eq!(option_series_strike(&series, -10).unwrap(), 200_000);
+    }
+
+    #[test]
+    fn test_covered_call_settlements() {
+        // Marked at 200, the buyer gets the 35% in the money
+        let spot = covered_call(PriceKind::Spot);
+        assert_eq!(
+            covered_call_settlements(&spot, &expiry(200_0000_0000)).unwrap(),
+            [650_000_000, 350_000_000]
+        );
+        assert_eq!(
+            covered_call_settlements(&spot, &expiry(100_0000_0000)).unwrap(),
+            [1_000_000_000, 0]
+        );
+        assert_eq!(
+            covered_call_settlements(&spot, &expiry(0)).unwrap_err(),
+            ErrorCode::OptionNotMarked.into()
+        );
+
+        // Averaged calls ignore the spot mark until the expiry is finalized
+        let average = covered_call(PriceKind::Average);
+        let mut data = expiry(200_0000_0000);
+        assert_eq!(
+            covered_call_settlements(&average, &data).unwrap_err(),
+            ErrorCode::OptionNotMarked.into()
+        );
+        data.average_price = 260_0000_0000;
+        data.is_finalized = true;
+        assert_eq!(
+            covered_call_settlements(&average, &data).unwrap(),
+            [500_000_000, 500_000_000]
+        );
+    }
+
+    #[test]
+    fn test_cash_secured_put_settlements() {
+        // Marked at 100, the buyer gets the 30 USDC in the money
+        let data = cash_secured_put();
+        assert_eq!(
+            cash_secured_put_settlements(&data, &expiry(100_0000_0000)).unwrap(),
+            [100_000_000, 30_000_000]
+        );
+        assert_eq!(
+            cash_secured_put_settlements(&data, &expiry(200_0000_0000)).unwrap(),
+            [130_000_000, 0]
+        );
+        assert_eq!(
+            cash_secured_put_settlements(&data, &expiry(0)).unwrap_err(),
+            ErrorCode::InvalidOraclePrice.into()
+        );
+    }
+}
@@ -2,3 +2,50 @@ use anchor_lang::prelude::*;
 
 #[constant]
 pub const SEED: &str = "anchor";
+
+// Most negative pyth exponent a strike must be representable at
+#[constant]
+pub const MIN_PRICE_EXPONENT: i32 = -12;
+
+// Oldest live price accepted for early exercise, in seconds
+#[constant]
+pub const MAX_PRICE_AGE_EARLY_EXERCISE: u64 = 30;
+
+// Most scheduled exercise dates a Bermudan option can carry
+#[constant]
+pub const MAX_EXERCISE_DATES: usize = 8;
+
+// Shortest gap between publish times of averaged mark samples, in seconds
+#[constant]
+pub const MIN_SAMPLE_SPACING: i64 = 60;
+
+// Most averaged samples per mark, one per MIN_SAMPLE_SPACING slot of the mark window
+#[constant]
+pub const MAX_MARK_SAMPLES: i64 = 128;
+
+// Seconds after expiry in which a mark can still move to a later print, after which it is fixed
+#[constant]
+pub const MARK_GRACE_PERIOD: i64 = 5 * 60;
+
+// Seconds after the grace period that an expired mark is kept for options to settle on
+#[constant]
+pub const MARK_RETENTION_PERIOD: i64 = 30 * 24 * 60 * 60;
+
+// Most mints the config can exempt from the extension check
+#[constant]
+pub const MAX_ALLOWED_MINTS: usize = 16;
+
+// Bits of the config pause mask. Closing an unbought option and redeeming writer tokens are never
+// paused
+#[constant]
+pub const PAUSE_INITIALIZE: u32 = 1;
+#[constant]
+pub const PAUSE_BUY: u32 = 2;
+#[constant]
+pub const PAUSE_WRITE: u32 = 4;
+#[constant]
+pub const PAUSE_MARK: u32 = 8;
+#[constant]
+pub const PAUSE_EXERCISE: u32 = 16;
+#[constant]
+pub const PAUSE_ALL: u32 = u32::MAX;
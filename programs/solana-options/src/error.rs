@@ -20,4 +20,46 @@ pub enum ErrorCode {
     PriceIrrelevant,
     #[msg("Option not marked")]
     OptionNotMarked,
+    #[msg("Premium is below the asking price")]
+    PremiumTooLow,
+    #[msg("Option uses a different settlement mode")]
+    InvalidSettlementMode,
+    #[msg("Quote accounts are required to close")]
+    QuoteAccountsRequired,
+    #[msg("Math overflow")]
+    MathOverflow,
+    #[msg("Oracle price is invalid")]
+    InvalidOraclePrice,
+    #[msg("Oracle confidence interval is too wide")]
+    ConfidenceTooWide,
+    #[msg("Option uses a different exercise style")]
+    InvalidExerciseStyle,
+    #[msg("Option is out of the money")]
+    OptionOutOfTheMoney,
+    #[msg("Exercise date is not scheduled or has been superseded")]
+    InvalidExerciseDate,
+    #[msg("Expiry mark is already finalized")]
+    MarkFinalized,
+    #[msg("Config parameter is out of range")]
+    InvalidConfig,
+    #[msg("Market parameter is out of range")]
+    InvalidMarket,
+    #[msg("Instruction is paused")]
+    ProtocolPaused,
+    #[msg("Expiry is outside the range allowed for the market")]
+    ExpiryOutOfRange,
+    #[msg("Amount is below the market minimum")]
+    AmountTooSmall,
+    #[msg("Mint has an extension that is not allowed")]
+    MintExtensionNotAllowed,
+    #[msg("Expiry mark can still change")]
+    MarkNotFinal,
+    #[msg("Expiry mark can't be closed while options may settle on it")]
+    MarkExpired,
+    #[msg("Not enough samples to average the mark")]
+    NotEnoughSamples,
+    #[msg("Fee vault is required to charge a fee")]
+    FeeVaultRequired,
+    #[msg("Averaged prices are only supported for European options")]
+    InvalidPriceKind,
 }
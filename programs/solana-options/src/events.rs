@@ -0,0 +1,198 @@
+use anchor_lang::prelude::*;
+
+use crate::{ConfigParams, ExerciseStyle, MarketParams, PriceKind, SettlementMode};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum OptionKind {
+    CoveredCall,
+    CashSecuredPut,
+}
+
+#[event]
+pub struct OptionCreated {
+    pub option: Pubkey,
+    pub kind: OptionKind,
+    pub seller: Pubkey,
+    pub buyer: Pubkey,
+    pub mint_base: Pubkey,
+    pub mint_quote: Pubkey,
+    pub mint_premium: Pubkey,
+    pub amount_base: u64,
+    pub amount_quote: u64,
+    pub amount_premium_ask: u64,
+    pub timestamp_expiry: i64,
+    pub settlement: SettlementMode,
+    pub style: ExerciseStyle,
+    pub exercise_dates: Vec<i64>,
+    pub price_kind: PriceKind,
+    pub feed_id: [u8; 32],
+}
+
+#[event]
+pub struct OptionBought {
+    pub option: Pubkey,
+    pub buyer: Pubkey,
+    pub payer: Pubkey,
+    pub mint_premium: Pubkey,
+    pub amount_premium: u64,
+    pub fee: u64,
+}
+
+// Cash splits are in the collateral mint. Physical exercise has no mark,
+// the seller receives amount_quote and the buyer amount_base
+#[event]
+pub struct OptionExercised {
+    pub option: Pubkey,
+    pub buyer: Pubkey,
+    pub settlement: SettlementMode,
+    pub strike: i64,
+    pub exponent: i32,
+    pub mark: Option<i64>,
+    pub amount_seller: u64,
+    pub amount_buyer: u64,
+    pub fee: u64,
+}
+
+#[event]
+pub struct OptionAutoExercised {
+    pub option: Pubkey,
+    pub caller: Pubkey,
+    pub bounty: u64,
+}
+
+#[event]
+pub struct OptionClosed {
+    pub option: Pubkey,
+    pub seller: Pubkey,
+    pub payer: Pubkey,
+    pub mark: Option<i64>,
+    pub amount_base: u64,
+    pub amount_quote: u64,
+}
+
+#[event]
+pub struct ExpiryMarked {
+    pub expiry: Pubkey,
+    pub feed_id: [u8; 32],
+    pub timestamp_expiry: i64,
+    pub price: i64,
+    pub conf: u64,
+    pub exponent: i32,
+    pub publish_time: i64,
+    pub payer: Pubkey,
+    pub sample_count: u32,
+}
+
+#[event]
+pub struct ExpiryFinalized {
+    pub expiry: Pubkey,
+    pub feed_id: [u8; 32],
+    pub timestamp_expiry: i64,
+    pub average_price: i64,
+    pub exponent: i32,
+    pub sample_count: u32,
+}
+
+#[event]
+pub struct ExpiryMarkClosed {
+    pub expiry: Pubkey,
+    pub feed_id: [u8; 32],
+    pub timestamp_expiry: i64,
+    pub payer: Pubkey,
+}
+
+#[event]
+pub struct SeriesCreated {
+    pub series: Pubkey,
+    pub mint_base: Pubkey,
+    pub mint_quote: Pubkey,
+    pub mint_option: Pubkey,
+    pub mint_writer: Pubkey,
+    pub amount_base: u64,
+    pub amount_quote: u64,
+    pub timestamp_expiry: i64,
+    pub feed_id: [u8; 32],
+    pub price_kind: PriceKind,
+}
+
+#[event]
+pub struct SeriesWritten {
+    pub series: Pubkey,
+    pub seller: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct SeriesExercised {
+    pub series: Pubkey,
+    pub buyer: Pubkey,
+    pub strike: i64,
+    pub exponent: i32,
+    pub mark: i64,
+    pub amount: u64,
+    pub amount_buyer: u64,
+    pub fee: u64,
+}
+
+#[event]
+pub struct SeriesRedeemed {
+    pub series: Pubkey,
+    pub seller: Pubkey,
+    pub strike: i64,
+    pub exponent: i32,
+    pub mark: i64,
+    pub amount: u64,
+    pub amount_seller: u64,
+}
+
+#[event]
+pub struct BuyerTransferred {
+    pub option: Pubkey,
+    pub from: Pubkey,
+    pub to: Pubkey,
+}
+
+#[event]
+pub struct SellerTransferred {
+    pub option: Pubkey,
+    pub from: Pubkey,
+    pub to: Pubkey,
+}
+
+#[event]
+pub struct ConfigUpdated {
+    pub config: Pubkey,
+    pub admin: Pubkey,
+    pub params: ConfigParams,
+}
+
+#[event]
+pub struct MarketUpdated {
+    pub market: Pubkey,
+    pub mint_base: Pubkey,
+    pub mint_quote: Pubkey,
+    pub feed_id: [u8; 32],
+    pub params: MarketParams,
+}
+
+#[event]
+pub struct PauseUpdated {
+    pub config: Pubkey,
+    pub admin: Pubkey,
+    pub paused: u32,
+}
+
+#[event]
+pub struct AdminTransferred {
+    pub config: Pubkey,
+    pub from: Pubkey,
+    pub to: Pubkey,
+}
+
+#[event]
+pub struct FeesWithdrawn {
+    pub config: Pubkey,
+    pub mint: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+}
@@ -0,0 +1,217 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::events::{OptionAutoExercised, OptionExercised};
+use crate::math::{calc_bps, calc_strike, get_settlements};
+use crate::state::{Config, CoveredCall, SettlementMode};
+use crate::token::{pay_out_with_fee, transfer_checked};
+use crate::{error::ErrorCode, ExpiryData, PAUSE_EXERCISE};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AutoExercise<'info> {
+    #[account(mut)]
+    pub caller: Signer<'info>,
+    #[account( constraint = buyer.key() == data.buyer)]
+    pub buyer: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [
+            "covered-call".as_bytes(),
+            data.creator.as_ref(),
+            &data.nonce.to_le_bytes(),
+        ],
+        bump = data.bump,
+    )]
+    pub data: Account<'info, CoveredCall>,
+    #[account(
+        seeds = [
+          "expiry-meta".as_bytes(),
+          &data.feed_id,
+          &data.timestamp_expiry.to_le_bytes(),
+        ],
+        bump = expiry.bump,
+    )]
+    pub expiry: Account<'info, ExpiryData>,
+    #[account( constraint = mint_base.key() == data.mint_base)]
+    pub mint_base: InterfaceAccount<'info, Mint>,
+    #[account(
+        init_if_needed,
+        payer = caller,
+        associated_token::mint = mint_base,
+        associated_token::authority = buyer,
+        associated_token::token_program = token_program,
+    )]
+    pub ata_buyer_base: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = caller,
+        associated_token::mint = mint_base,
+        associated_token::authority = caller,
+        associated_token::token_program = token_program,
+    )]
+    pub ata_caller_base: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint_base,
+        associated_token::authority = data,
+        associated_token::token_program = token_program,
+    )]
+    pub ata_vault_base: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        seeds = ["config".as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+    // Created with the market, only needed when a fee is charged
+    #[account(
+        mut,
+        seeds = [b"fee-vault", mint_base.key().as_ref()],
+        bump,
+        token::mint = mint_base,
+        token::authority = config,
+        token::token_program = token_program,
+    )]
+    pub fee_vault_base: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+// Lets anyone settle an in the money option for an offline buyer, taking a bounty from the payout
+pub fn handle_auto_exercise<'info>(
+    ctx: Context<'_, '_, '_, 'info, AutoExercise<'info>>,
+) -> Result<()> {
+    let clock = Clock::get()?;
+
+    require!(
+        !ctx.accounts.config.is_paused(PAUSE_EXERCISE),
+        ErrorCode::ProtocolPaused
+    );
+
+    require!(
+        clock.unix_timestamp >= ctx.accounts.data.timestamp_expiry,
+        ErrorCode::OptionNotExpired
+    );
+
+    require!(
+        ctx.accounts.data.settlement == SettlementMode::Cash,
+        ErrorCode::InvalidSettlementMode
+    );
+
+    require!(
+        ctx.accounts.data.amount_premium.is_some(),
+        ErrorCode::OptionNotPurchased
+    );
+
+    require!(
+        !ctx.accounts.data.is_exercised,
+        ErrorCode::OptionAlreadyExercised
+    );
+    // Incase expiry account was initialized but not set, or its average not finalized
+    ctx.accounts
+        .expiry
+        .settlement_price(ctx.accounts.data.price_kind)
+        .ok_or(error!(ErrorCode::OptionNotMarked))?;
+    // The seller closes on the same final mark, so neither side can pick an early print
+    let mark = ctx
+        .accounts
+        .expiry
+        .final_settlement_price(
+            ctx.accounts.data.price_kind,
+            ctx.accounts.data.timestamp_expiry,
+            clock.unix_timestamp,
+        )
+        .ok_or(ErrorCode::MarkNotFinal)?;
+
+    let strike = calc_strike(
+        ctx.accounts.data.amount_base,
+        ctx.accounts.data.amount_quote,
+        ctx.accounts.data.decimals_base,
+        ctx.accounts.data.decimals_quote,
+        ctx.accounts.expiry.exponent,
+    )?;
+
+    let [amount_seller, amount_itm] = get_settlements(strike, mark, ctx.accounts.data.amount_base)?;
+
+    // Out of the money options are left for the seller to close
+    require!(amount_itm > 0, ErrorCode::OptionOutOfTheMoney);
+
+    let bounty = calc_bps(amount_itm, ctx.accounts.config.params.bounty_bps)?;
+    let fee = calc_bps(amount_itm, ctx.accounts.config.params.fee_settlement_bps)?;
+    let amount_buyer = amount_itm - fee - bounty;
+
+    let seeds = [
+        "covered-call".as_bytes(),
+        ctx.accounts.data.creator.as_ref(),
+        &ctx.accounts.data.nonce.to_le_bytes(),
+        &[ctx.accounts.data.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    // Transfer base from vault to buyer, and the protocol fee
+    pay_out_with_fee(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.ata_vault_base.to_account_info(),
+                to: ctx.accounts.ata_buyer_base.to_account_info(),
+                mint: ctx.accounts.mint_base.to_account_info(),
+                authority: ctx.accounts.data.to_account_info(),
+            },
+            signer,
+        )
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+        ctx.accounts
+            .fee_vault_base
+            .as_ref()
+            .map(|x| x.to_account_info()),
+        amount_buyer,
+        fee,
+        ctx.accounts.mint_base.decimals,
+    )?;
+
+    // Transfer bounty from vault to caller
+    if bounty > 0 {
+        transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.ata_vault_base.to_account_info(),
+                    to: ctx.accounts.ata_caller_base.to_account_info(),
+                    mint: ctx.accounts.mint_base.to_account_info(),
+                    authority: ctx.accounts.data.to_account_info(),
+                },
+                signer,
+            )
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+            bounty,
+            ctx.accounts.mint_base.decimals,
+        )?;
+    }
+
+    ctx.accounts.data.is_exercised = true;
+
+    emit_cpi!(OptionExercised {
+        option: ctx.accounts.data.key(),
+        buyer: ctx.accounts.buyer.key(),
+        settlement: ctx.accounts.data.settlement,
+        strike,
+        exponent: ctx.accounts.expiry.exponent,
+        mark: Some(mark),
+        amount_seller,
+        amount_buyer,
+        fee,
+    });
+
+    emit_cpi!(OptionAutoExercised {
+        option: ctx.accounts.data.key(),
+        caller: ctx.accounts.caller.key(),
+        bounty,
+    });
+
+    Ok(())
+}
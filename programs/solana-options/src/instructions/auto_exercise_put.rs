@@ -0,0 +1,210 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::events::{OptionAutoExercised, OptionExercised};
+use crate::math::{calc_bps, calc_strike, get_put_settlements};
+use crate::state::{CashSecuredPut, Config, PriceKind, SettlementMode};
+use crate::token::{pay_out_with_fee, transfer_checked};
+use crate::{error::ErrorCode, ExpiryData, PAUSE_EXERCISE};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AutoExercisePut<'info> {
+    #[account(mut)]
+    pub caller: Signer<'info>,
+    #[account( constraint = buyer.key() == data.buyer)]
+    pub buyer: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [
+            "cash-secured-put".as_bytes(),
+            data.creator.as_ref(),
+            &data.nonce.to_le_bytes(),
+        ],
+        bump = data.bump,
+    )]
+    pub data: Account<'info, CashSecuredPut>,
+    #[account(
+        seeds = [
+          "expiry-meta".as_bytes(),
+          &data.feed_id,
+          &data.timestamp_expiry.to_le_bytes(),
+        ],
+        bump = expiry.bump,
+    )]
+    pub expiry: Account<'info, ExpiryData>,
+    #[account( constraint = mint_quote.key() == data.mint_quote)]
+    pub mint_quote: InterfaceAccount<'info, Mint>,
+    #[account(
+        init_if_needed,
+        payer = caller,
+        associated_token::mint = mint_quote,
+        associated_token::authority = buyer,
+        associated_token::token_program = token_program,
+    )]
+    pub ata_buyer_quote: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = caller,
+        associated_token::mint = mint_quote,
+        associated_token::authority = caller,
+        associated_token::token_program = token_program,
+    )]
+    pub ata_caller_quote: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint_quote,
+        associated_token::authority = data,
+        associated_token::token_program = token_program,
+    )]
+    pub ata_vault_quote: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        seeds = ["config".as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+    // Created with the market, only needed when a fee is charged
+    #[account(
+        mut,
+        seeds = [b"fee-vault", mint_quote.key().as_ref()],
+        bump,
+        token::mint = mint_quote,
+        token::authority = config,
+        token::token_program = token_program,
+    )]
+    pub fee_vault_quote: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+// Same as auto_exercise for puts, the buyer's payout and the bounty come out of the quote vault
+pub fn handle_auto_exercise_put<'info>(
+    ctx: Context<'_, '_, '_, 'info, AutoExercisePut<'info>>,
+) -> Result<()> {
+    let clock = Clock::get()?;
+
+    require!(
+        !ctx.accounts.config.is_paused(PAUSE_EXERCISE),
+        ErrorCode::ProtocolPaused
+    );
+
+    require!(
+        clock.unix_timestamp >= ctx.accounts.data.timestamp_expiry,
+        ErrorCode::OptionNotExpired
+    );
+
+    require!(
+        ctx.accounts.data.amount_premium.is_some(),
+        ErrorCode::OptionNotPurchased
+    );
+
+    require!(
+        !ctx.accounts.data.is_exercised,
+        ErrorCode::OptionAlreadyExercised
+    );
+    // Incase expiry account was initialized but not set
+    require!(ctx.accounts.expiry.price != 0, ErrorCode::OptionNotMarked);
+    // The seller closes on the same final mark, so neither side can pick an early print
+    let mark = ctx
+        .accounts
+        .expiry
+        .final_settlement_price(
+            PriceKind::Spot,
+            ctx.accounts.data.timestamp_expiry,
+            clock.unix_timestamp,
+        )
+        .ok_or(ErrorCode::MarkNotFinal)?;
+
+    let strike = calc_strike(
+        ctx.accounts.data.amount_base,
+        ctx.accounts.data.amount_quote,
+        ctx.accounts.data.decimals_base,
+        ctx.accounts.data.decimals_quote,
+        ctx.accounts.expiry.exponent,
+    )?;
+
+    let [amount_seller, amount_itm] =
+        get_put_settlements(strike, mark, ctx.accounts.data.amount_quote)?;
+
+    // Out of the money options are left for the seller to close
+    require!(amount_itm > 0, ErrorCode::OptionOutOfTheMoney);
+
+    let bounty = calc_bps(amount_itm, ctx.accounts.config.params.bounty_bps)?;
+    let fee = calc_bps(amount_itm, ctx.accounts.config.params.fee_settlement_bps)?;
+    let amount_buyer = amount_itm - fee - bounty;
+
+    let seeds = [
+        "cash-secured-put".as_bytes(),
+        ctx.accounts.data.creator.as_ref(),
+        &ctx.accounts.data.nonce.to_le_bytes(),
+        &[ctx.accounts.data.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    // Transfer quote from vault to buyer, and the protocol fee
+    pay_out_with_fee(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.ata_vault_quote.to_account_info(),
+                to: ctx.accounts.ata_buyer_quote.to_account_info(),
+                mint: ctx.accounts.mint_quote.to_account_info(),
+                authority: ctx.accounts.data.to_account_info(),
+            },
+            signer,
+        )
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+        ctx.accounts
+            .fee_vault_quote
+            .as_ref()
+            .map(|x| x.to_account_info()),
+        amount_buyer,
+        fee,
+        ctx.accounts.mint_quote.decimals,
+    )?;
+
+    // Transfer bounty from vault to caller
+    if bounty > 0 {
+        transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.ata_vault_quote.to_account_info(),
+                    to: ctx.accounts.ata_caller_quote.to_account_info(),
+                    mint: ctx.accounts.mint_quote.to_account_info(),
+                    authority: ctx.accounts.data.to_account_info(),
+                },
+                signer,
+            )
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+            bounty,
+            ctx.accounts.mint_quote.decimals,
+        )?;
+    }
+
+    ctx.accounts.data.is_exercised = true;
+
+    emit_cpi!(OptionExercised {
+        option: ctx.accounts.data.key(),
+        buyer: ctx.accounts.buyer.key(),
+        settlement: SettlementMode::Cash,
+        strike,
+        exponent: ctx.accounts.expiry.exponent,
+        mark: Some(mark),
+        amount_seller,
+        amount_buyer,
+        fee,
+    });
+
+    emit_cpi!(OptionAutoExercised {
+        option: ctx.accounts.data.key(),
+        caller: ctx.accounts.caller.key(),
+        bounty,
+    });
+
+    Ok(())
+}
@@ -1,57 +1,88 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{transfer_checked, Mint, Token, TokenAccount, TransferChecked},
+    token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked},
 };
 
 use crate::error::ErrorCode;
-use crate::state::CoveredCall;
+use crate::events::OptionBought;
+use crate::math::calc_bps;
+use crate::state::{Config, CoveredCall};
+use crate::token::transfer_checked;
+use crate::PAUSE_BUY;
 
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(amount_premium: u64)]
 pub struct Buy<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
-    #[account(mut, constraint = buyer.key() == data.buyer)]
+    #[account(constraint = seller.key() == data.seller)]
+    pub seller: SystemAccount<'info>,
+    #[account(
+        mut,
+        constraint = data.buyer == Pubkey::default() || buyer.key() == data.buyer,
+    )]
     pub buyer: SystemAccount<'info>,
     #[account(
         mut,
         seeds = [
-            "covered-call".as_bytes(), 
-            data.seller.as_ref(),
-            buyer.key().as_ref(),
-            data.mint_base.as_ref(),
-            data.mint_quote.as_ref(),
-            &data.amount_base.to_le_bytes(),
-            &data.amount_quote.to_le_bytes(),
-            &data.timestamp_expiry.to_le_bytes(),
+            "covered-call".as_bytes(),
+            data.creator.as_ref(),
+            &data.nonce.to_le_bytes(),
         ],
         bump = data.bump,
     )]
     pub data: Account<'info, CoveredCall>,
-    #[account( constraint = mint_premium.key() == data.mint_base)]
-    pub mint_premium: Account<'info, Mint>,
+    #[account( constraint = mint_premium.key() == data.mint_premium)]
+    pub mint_premium: InterfaceAccount<'info, Mint>,
     #[account(
         mut,
         constraint = ata_payer_premium.amount >= amount_premium,
         associated_token::mint = mint_premium,
         associated_token::authority = payer,
+        associated_token::token_program = token_program,
     )]
-    pub ata_payer_premium: Account<'info, TokenAccount>, // This already exists because we enforce it to be base
+    pub ata_payer_premium: InterfaceAccount<'info, TokenAccount>,
     #[account(
-        mut,
+        init_if_needed,
+        payer = payer,
         associated_token::mint = mint_premium,
-        associated_token::authority = data,
+        associated_token::authority = seller,
+        associated_token::token_program = token_program,
+    )]
+    pub ata_seller_premium: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        seeds = ["config".as_bytes()],
+        bump = config.bump,
     )]
-    pub ata_vault_premium: Account<'info, TokenAccount>,
+    pub config: Account<'info, Config>,
+    // Created with the market, only needed when a fee is charged
+    #[account(
+        mut,
+        seeds = [b"fee-vault", mint_premium.key().as_ref()],
+        bump,
+        token::mint = mint_premium,
+        token::authority = config,
+        token::token_program = token_program,
+    )]
+    pub fee_vault_premium: Option<InterfaceAccount<'info, TokenAccount>>,
     pub associated_token_program: Program<'info, AssociatedToken>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
 
-pub fn handle_buy(ctx: Context<Buy>, amount_premium: u64) -> Result<()> {
+pub fn handle_buy<'info>(
+    ctx: Context<'_, '_, '_, 'info, Buy<'info>>,
+    amount_premium: u64,
+) -> Result<()> {
     let clock = Clock::get()?;
 
+    require!(
+        !ctx.accounts.config.is_paused(PAUSE_BUY),
+        ErrorCode::ProtocolPaused
+    );
+
     require!(
         clock.unix_timestamp <= ctx.accounts.data.timestamp_expiry,
         ErrorCode::OptionExpired
@@ -61,22 +92,64 @@ pub fn handle_buy(ctx: Context<Buy>, amount_premium: u64) -> Result<()> {
         ctx.accounts.data.amount_premium.is_none(),
         ErrorCode::OptionAlreadyBought
     );
+
+    require!(
+        amount_premium >= ctx.accounts.data.amount_premium_ask,
+        ErrorCode::PremiumTooLow
+    );
     ctx.accounts.data.amount_premium = Some(amount_premium);
+    // First buyer of an open offer takes the long side
+    ctx.accounts.data.buyer = ctx.accounts.buyer.key();
 
-    // Transfer premium in base to vault
+    let fee = calc_bps(amount_premium, ctx.accounts.config.params.fee_premium_bps)?;
+
+    // Transfer premium straight to seller, the vault only holds collateral
     transfer_checked(
         CpiContext::new(
             ctx.accounts.token_program.to_account_info(),
             TransferChecked {
                 from: ctx.accounts.ata_payer_premium.to_account_info(),
-                to: ctx.accounts.ata_vault_premium.to_account_info(),
+                to: ctx.accounts.ata_seller_premium.to_account_info(),
                 mint: ctx.accounts.mint_premium.to_account_info(),
                 authority: ctx.accounts.payer.to_account_info(),
             },
-        ),
-        amount_premium,
+        )
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+        amount_premium - fee,
         ctx.accounts.mint_premium.decimals,
     )?;
 
+    // Transfer protocol fee from payer
+    if fee > 0 {
+        let fee_vault = ctx
+            .accounts
+            .fee_vault_premium
+            .as_ref()
+            .ok_or(ErrorCode::FeeVaultRequired)?;
+        transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.ata_payer_premium.to_account_info(),
+                    to: fee_vault.to_account_info(),
+                    mint: ctx.accounts.mint_premium.to_account_info(),
+                    authority: ctx.accounts.payer.to_account_info(),
+                },
+            )
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+            fee,
+            ctx.accounts.mint_premium.decimals,
+        )?;
+    }
+
+    emit_cpi!(OptionBought {
+        option: ctx.accounts.data.key(),
+        buyer: ctx.accounts.buyer.key(),
+        payer: ctx.accounts.payer.key(),
+        mint_premium: ctx.accounts.mint_premium.key(),
+        amount_premium,
+        fee,
+    });
+
     Ok(())
 }
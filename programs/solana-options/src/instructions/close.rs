@@ -1,34 +1,29 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{
-        close_account, transfer_checked, CloseAccount, Mint, Token, TokenAccount, TransferChecked,
+    token_interface::{
+        close_account, CloseAccount, Mint, TokenAccount, TokenInterface, TransferChecked,
     },
 };
 
 use crate::math::calc_strike;
-use crate::state::CoveredCall;
-use crate::{error::ErrorCode, ExpiryData};
+use crate::state::{CoveredCall, SettlementMode};
+use crate::token::{harvest_transfer_fees, transfer_checked};
+use crate::{error::ErrorCode, events::OptionClosed, ExpiryData, MARK_GRACE_PERIOD};
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct Close<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
     #[account(mut, constraint = seller.key() == data.seller)]
     pub seller: SystemAccount<'info>,
-    #[account(mut, constraint = buyer.key() == data.buyer)]
-    pub buyer: SystemAccount<'info>,
     #[account(
         mut,
         seeds = [
-            "covered-call".as_bytes(), 
-            seller.key().as_ref(),
-            buyer.key().as_ref(),
-            mint_base.key().as_ref(),
-            &data.mint_quote.as_ref(),
-            &data.amount_base.to_le_bytes(),
-            &data.amount_quote.to_le_bytes(),
-            &data.timestamp_expiry.to_le_bytes(),
+            "covered-call".as_bytes(),
+            data.creator.as_ref(),
+            &data.nonce.to_le_bytes(),
         ],
         bump = data.bump,
         close = seller,
@@ -37,63 +32,158 @@ pub struct Close<'info> {
     #[account(
         seeds = [
           "expiry-meta".as_bytes(),
+           &data.feed_id,
            &data.timestamp_expiry.to_le_bytes(),
         ],
         bump = expiry.bump,
     )]
     pub expiry: Option<Account<'info, ExpiryData>>,
-    #[account( constraint = mint_base.key() == data.mint_base)]
-    pub mint_base: Account<'info, Mint>,
+    #[account(mut, constraint = mint_base.key() == data.mint_base)]
+    pub mint_base: InterfaceAccount<'info, Mint>,
+    // A transferred seller may not hold the collateral mint yet
     #[account(
-        mut,
-        associated_token::mint = data.mint_base,
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint_base,
         associated_token::authority = seller,
+        associated_token::token_program = token_program,
     )]
-    pub ata_seller_base: Account<'info, TokenAccount>,
+    pub ata_seller_base: InterfaceAccount<'info, TokenAccount>,
     #[account(
         mut,
         associated_token::mint = mint_base,
         associated_token::authority = data,
+        associated_token::token_program = token_program,
+    )]
+    pub ata_vault_base: InterfaceAccount<'info, TokenAccount>,
+    // Quote accounts are only required once a physically settled option is exercised
+    #[account(mut, constraint = mint_quote.key() == data.mint_quote)]
+    pub mint_quote: Option<InterfaceAccount<'info, Mint>>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint_quote,
+        associated_token::authority = seller,
+        associated_token::token_program = token_program_quote,
+    )]
+    pub ata_seller_quote: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_quote,
+        associated_token::authority = data,
+        associated_token::token_program = token_program_quote,
     )]
-    pub ata_vault_base: Account<'info, TokenAccount>,
+    pub ata_vault_quote: Option<InterfaceAccount<'info, TokenAccount>>,
     pub associated_token_program: Program<'info, AssociatedToken>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub token_program_quote: Option<Interface<'info, TokenInterface>>,
     pub system_program: Program<'info, System>,
 }
 
-pub fn handle_close(ctx: Context<Close>) -> Result<()> {
+pub fn handle_close<'info>(ctx: Context<'_, '_, '_, 'info, Close<'info>>) -> Result<()> {
     let clock = Clock::get()?;
 
     let seeds = [
         "covered-call".as_bytes(),
-        ctx.accounts.data.seller.as_ref(),
-        ctx.accounts.data.buyer.as_ref(),
-        ctx.accounts.data.mint_base.as_ref(),
-        ctx.accounts.data.mint_quote.as_ref(),
-        &ctx.accounts.data.amount_base.to_le_bytes(),
-        &ctx.accounts.data.amount_quote.to_le_bytes(),
-        &ctx.accounts.data.timestamp_expiry.to_le_bytes(),
+        ctx.accounts.data.creator.as_ref(),
+        &ctx.accounts.data.nonce.to_le_bytes(),
         &[ctx.accounts.data.bump],
     ];
     let signer = &[&seeds[..]];
 
-    let strike = calc_strike(
-        ctx.accounts.data.amount_base,
-        ctx.accounts.data.amount_quote,
-    );
     let is_expired = clock.unix_timestamp >= ctx.accounts.data.timestamp_expiry;
     let is_exercised = ctx.accounts.data.is_exercised;
-    let is_otm = ctx
+    // Only a final mark, so an early out of the money print can't sweep the collateral
+    let mark = ctx.accounts.expiry.as_ref().and_then(|x| {
+        x.final_settlement_price(
+            ctx.accounts.data.price_kind,
+            ctx.accounts.data.timestamp_expiry,
+            clock.unix_timestamp,
+        )
+    });
+    let is_otm = match (&ctx.accounts.expiry, mark) {
+        (Some(x), Some(mark)) if mark > 0 => {
+            let strike = calc_strike(
+                ctx.accounts.data.amount_base,
+                ctx.accounts.data.amount_quote,
+                ctx.accounts.data.decimals_base,
+                ctx.accounts.data.decimals_quote,
+                x.exponent,
+            )?;
+            mark <= strike
+        }
+        _ => false,
+    };
+
+    // Physical options need no mark, the buyer just loses the right once the window has passed
+    let deadline = ctx
         .accounts
-        .expiry
-        .as_ref()
-        .is_some_and(|x| x.price > 0 && x.price <= strike);
+        .data
+        .timestamp_expiry
+        .saturating_add(MARK_GRACE_PERIOD);
+    let is_lapsed = ctx.accounts.data.settlement == SettlementMode::Physical
+        && clock.unix_timestamp >= deadline;
 
     require!(
-        (is_expired && (is_exercised || is_otm)) || ctx.accounts.data.amount_premium.is_none(),
+        is_exercised
+            || (is_expired && (is_otm || is_lapsed))
+            || ctx.accounts.data.amount_premium.is_none(),
         ErrorCode::OptionCannotBeClosedYet,
     );
 
+    let mut amount_quote = 0;
+
+    // Transfer quote paid on physical exercise to seller
+    if ctx.accounts.data.settlement == SettlementMode::Physical && is_exercised {
+        let (
+            Some(mint_quote),
+            Some(ata_seller_quote),
+            Some(ata_vault_quote),
+            Some(token_program_quote),
+        ) = (
+            &ctx.accounts.mint_quote,
+            &ctx.accounts.ata_seller_quote,
+            &ctx.accounts.ata_vault_quote,
+            &ctx.accounts.token_program_quote,
+        )
+        else {
+            return err!(ErrorCode::QuoteAccountsRequired);
+        };
+        amount_quote = ata_vault_quote.amount;
+
+        transfer_checked(
+            CpiContext::new_with_signer(
+                token_program_quote.to_account_info(),
+                TransferChecked {
+                    from: ata_vault_quote.to_account_info(),
+                    to: ata_seller_quote.to_account_info(),
+                    mint: mint_quote.to_account_info(),
+                    authority: ctx.accounts.data.to_account_info(),
+                },
+                signer,
+            )
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+            ata_vault_quote.amount,
+            mint_quote.decimals,
+        )?;
+
+        harvest_transfer_fees(
+            token_program_quote.to_account_info(),
+            mint_quote.to_account_info(),
+            ata_vault_quote.to_account_info(),
+        )?;
+
+        close_account(CpiContext::new_with_signer(
+            token_program_quote.to_account_info(),
+            CloseAccount {
+                account: ata_vault_quote.to_account_info(),
+                destination: ctx.accounts.seller.to_account_info(),
+                authority: ctx.accounts.data.to_account_info(),
+            },
+            signer,
+        ))?;
+    }
+
     // Transfer base to seller
     if ctx.accounts.ata_vault_base.amount > 0 {
         transfer_checked(
@@ -106,12 +196,19 @@ pub fn handle_close(ctx: Context<Close>) -> Result<()> {
                     authority: ctx.accounts.data.to_account_info(),
                 },
                 signer,
-            ),
+            )
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
             ctx.accounts.ata_vault_base.amount,
             ctx.accounts.mint_base.decimals,
         )?;
     }
 
+    harvest_transfer_fees(
+        ctx.accounts.token_program.to_account_info(),
+        ctx.accounts.mint_base.to_account_info(),
+        ctx.accounts.ata_vault_base.to_account_info(),
+    )?;
+
     close_account(CpiContext::new_with_signer(
         ctx.accounts.token_program.to_account_info(),
         CloseAccount {
@@ -122,5 +219,14 @@ pub fn handle_close(ctx: Context<Close>) -> Result<()> {
         signer,
     ))?;
 
+    emit_cpi!(OptionClosed {
+        option: ctx.accounts.data.key(),
+        seller: ctx.accounts.seller.key(),
+        payer: ctx.accounts.payer.key(),
+        mark,
+        amount_base: ctx.accounts.ata_vault_base.amount,
+        amount_quote,
+    });
+
     Ok(())
 }
@@ -0,0 +1,149 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        close_account, CloseAccount, Mint, TokenAccount, TokenInterface, TransferChecked,
+    },
+};
+
+use crate::math::calc_strike;
+use crate::state::{CashSecuredPut, PriceKind};
+use crate::token::{harvest_transfer_fees, transfer_checked};
+use crate::{error::ErrorCode, events::OptionClosed, ExpiryData};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClosePut<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut, constraint = seller.key() == data.seller)]
+    pub seller: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [
+            "cash-secured-put".as_bytes(),
+            data.creator.as_ref(),
+            &data.nonce.to_le_bytes(),
+        ],
+        bump = data.bump,
+        close = seller,
+    )]
+    pub data: Account<'info, CashSecuredPut>,
+    #[account(
+        seeds = [
+          "expiry-meta".as_bytes(),
+           &data.feed_id,
+           &data.timestamp_expiry.to_le_bytes(),
+        ],
+        bump = expiry.bump,
+    )]
+    pub expiry: Option<Account<'info, ExpiryData>>,
+    #[account(mut, constraint = mint_quote.key() == data.mint_quote)]
+    pub mint_quote: InterfaceAccount<'info, Mint>,
+    // A transferred seller may not hold the collateral mint yet
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint_quote,
+        associated_token::authority = seller,
+        associated_token::token_program = token_program,
+    )]
+    pub ata_seller_quote: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint_quote,
+        associated_token::authority = data,
+        associated_token::token_program = token_program,
+    )]
+    pub ata_vault_quote: InterfaceAccount<'info, TokenAccount>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_close_put<'info>(ctx: Context<'_, '_, '_, 'info, ClosePut<'info>>) -> Result<()> {
+    let clock = Clock::get()?;
+
+    let seeds = [
+        "cash-secured-put".as_bytes(),
+        ctx.accounts.data.creator.as_ref(),
+        &ctx.accounts.data.nonce.to_le_bytes(),
+        &[ctx.accounts.data.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    let is_expired = clock.unix_timestamp >= ctx.accounts.data.timestamp_expiry;
+    let is_exercised = ctx.accounts.data.is_exercised;
+    // Only a final mark, so an early out of the money print can't sweep the collateral
+    let mark = ctx.accounts.expiry.as_ref().and_then(|x| {
+        x.final_settlement_price(
+            PriceKind::Spot,
+            ctx.accounts.data.timestamp_expiry,
+            clock.unix_timestamp,
+        )
+    });
+    let is_otm = match (&ctx.accounts.expiry, mark) {
+        (Some(x), Some(mark)) if mark > 0 => {
+            let strike = calc_strike(
+                ctx.accounts.data.amount_base,
+                ctx.accounts.data.amount_quote,
+                ctx.accounts.data.decimals_base,
+                ctx.accounts.data.decimals_quote,
+                x.exponent,
+            )?;
+            mark >= strike
+        }
+        _ => false,
+    };
+
+    require!(
+        (is_expired && (is_exercised || is_otm)) || ctx.accounts.data.amount_premium.is_none(),
+        ErrorCode::OptionCannotBeClosedYet,
+    );
+
+    // Transfer quote to seller
+    if ctx.accounts.ata_vault_quote.amount > 0 {
+        transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.ata_vault_quote.to_account_info(),
+                    to: ctx.accounts.ata_seller_quote.to_account_info(),
+                    mint: ctx.accounts.mint_quote.to_account_info(),
+                    authority: ctx.accounts.data.to_account_info(),
+                },
+                signer,
+            )
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+            ctx.accounts.ata_vault_quote.amount,
+            ctx.accounts.mint_quote.decimals,
+        )?;
+    }
+
+    harvest_transfer_fees(
+        ctx.accounts.token_program.to_account_info(),
+        ctx.accounts.mint_quote.to_account_info(),
+        ctx.accounts.ata_vault_quote.to_account_info(),
+    )?;
+
+    close_account(CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        CloseAccount {
+            account: ctx.accounts.ata_vault_quote.to_account_info(),
+            destination: ctx.accounts.seller.to_account_info(),
+            authority: ctx.accounts.data.to_account_info(),
+        },
+        signer,
+    ))?;
+
+    emit_cpi!(OptionClosed {
+        option: ctx.accounts.data.key(),
+        seller: ctx.accounts.seller.key(),
+        payer: ctx.accounts.payer.key(),
+        mark,
+        amount_base: 0,
+        amount_quote: ctx.accounts.ata_vault_quote.amount,
+    });
+
+    Ok(())
+}
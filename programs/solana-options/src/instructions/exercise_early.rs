@@ -0,0 +1,185 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+
+use crate::error::ErrorCode;
+use crate::events::OptionExercised;
+use crate::math::{calc_bps, calc_strike, get_settlements};
+use crate::state::{Config, CoveredCall, ExerciseStyle, SettlementMode};
+use crate::token::pay_out_with_fee;
+use crate::{MAX_PRICE_AGE_EARLY_EXERCISE, MIN_PRICE_EXPONENT, PAUSE_EXERCISE};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ExerciseEarly<'info> {
+    #[account(mut, constraint = buyer.key() == data.buyer)]
+    pub buyer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            "covered-call".as_bytes(),
+            data.creator.as_ref(),
+            &data.nonce.to_le_bytes(),
+        ],
+        bump = data.bump,
+    )]
+    pub data: Account<'info, CoveredCall>,
+    pub price_update: Account<'info, PriceUpdateV2>,
+    #[account( constraint = mint_base.key() == data.mint_base)]
+    pub mint_base: InterfaceAccount<'info, Mint>,
+    // A buyer who received the option by transfer may not hold the mint yet
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        associated_token::mint = mint_base,
+        associated_token::authority = buyer,
+        associated_token::token_program = token_program,
+    )]
+    pub ata_buyer_base: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint_base,
+        associated_token::authority = data,
+        associated_token::token_program = token_program,
+    )]
+    pub ata_vault_base: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        seeds = ["config".as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+    // Created with the market, only needed when a fee is charged
+    #[account(
+        mut,
+        seeds = [b"fee-vault", mint_base.key().as_ref()],
+        bump,
+        token::mint = mint_base,
+        token::authority = config,
+        token::token_program = token_program,
+    )]
+    pub fee_vault_base: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_exercise_early<'info>(
+    ctx: Context<'_, '_, '_, 'info, ExerciseEarly<'info>>,
+) -> Result<()> {
+    let clock = Clock::get()?;
+
+    require!(
+        !ctx.accounts.config.is_paused(PAUSE_EXERCISE),
+        ErrorCode::ProtocolPaused
+    );
+
+    require!(
+        ctx.accounts.data.style == ExerciseStyle::American,
+        ErrorCode::InvalidExerciseStyle
+    );
+
+    // After expiry the option settles on the expiry mark through exercise
+    require!(
+        clock.unix_timestamp < ctx.accounts.data.timestamp_expiry,
+        ErrorCode::OptionExpired
+    );
+
+    require!(
+        ctx.accounts.data.settlement == SettlementMode::Cash,
+        ErrorCode::InvalidSettlementMode
+    );
+
+    require!(
+        ctx.accounts.data.amount_premium.is_some(),
+        ErrorCode::OptionNotPurchased
+    );
+
+    require!(
+        !ctx.accounts.data.is_exercised,
+        ErrorCode::OptionAlreadyExercised
+    );
+
+    let price = ctx.accounts.price_update.get_price_no_older_than(
+        &clock,
+        MAX_PRICE_AGE_EARLY_EXERCISE,
+        &ctx.accounts.data.feed_id,
+    )?;
+
+    require!(
+        price.price > 0 && price.exponent >= MIN_PRICE_EXPONENT,
+        ErrorCode::InvalidOraclePrice
+    );
+
+    require!(
+        u128::from(price.conf) * 10_000
+            <= u128::from(price.price.unsigned_abs())
+                * u128::from(ctx.accounts.config.params.max_conf_bps),
+        ErrorCode::ConfidenceTooWide
+    );
+
+    let strike = calc_strike(
+        ctx.accounts.data.amount_base,
+        ctx.accounts.data.amount_quote,
+        ctx.accounts.data.decimals_base,
+        ctx.accounts.data.decimals_quote,
+        price.exponent,
+    )?;
+
+    let [amount_seller, amount_itm] =
+        get_settlements(strike, price.price, ctx.accounts.data.amount_base)?;
+
+    // Exercising out of the money would only forfeit the option
+    require!(amount_itm > 0, ErrorCode::OptionOutOfTheMoney);
+
+    let fee = calc_bps(amount_itm, ctx.accounts.config.params.fee_settlement_bps)?;
+    let amount_buyer = amount_itm - fee;
+
+    let seeds = [
+        "covered-call".as_bytes(),
+        ctx.accounts.data.creator.as_ref(),
+        &ctx.accounts.data.nonce.to_le_bytes(),
+        &[ctx.accounts.data.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    // Transfer base from vault to buyer, and the protocol fee
+    pay_out_with_fee(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.ata_vault_base.to_account_info(),
+                to: ctx.accounts.ata_buyer_base.to_account_info(),
+                mint: ctx.accounts.mint_base.to_account_info(),
+                authority: ctx.accounts.data.to_account_info(),
+            },
+            signer,
+        )
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+        ctx.accounts
+            .fee_vault_base
+            .as_ref()
+            .map(|x| x.to_account_info()),
+        amount_buyer,
+        fee,
+        ctx.accounts.mint_base.decimals,
+    )?;
+
+    ctx.accounts.data.is_exercised = true;
+
+    emit_cpi!(OptionExercised {
+        option: ctx.accounts.data.key(),
+        buyer: ctx.accounts.buyer.key(),
+        settlement: ctx.accounts.data.settlement,
+        strike,
+        exponent: price.exponent,
+        mark: Some(price.price),
+        amount_seller,
+        amount_buyer,
+        fee,
+    });
+
+    Ok(())
+}
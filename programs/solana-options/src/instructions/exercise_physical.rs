@@ -0,0 +1,181 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::error::ErrorCode;
+use crate::events::OptionExercised;
+use crate::math::calc_strike;
+use crate::state::{Config, CoveredCall, SettlementMode};
+use crate::token::{get_amount_with_transfer_fee, transfer_checked};
+use crate::{MARK_GRACE_PERIOD, MIN_PRICE_EXPONENT, PAUSE_EXERCISE};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ExercisePhysical<'info> {
+    #[account(mut, constraint = buyer.key() == data.buyer)]
+    pub buyer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            "covered-call".as_bytes(),
+            data.creator.as_ref(),
+            &data.nonce.to_le_bytes(),
+        ],
+        bump = data.bump,
+    )]
+    pub data: Account<'info, CoveredCall>,
+    #[account( constraint = mint_base.key() == data.mint_base)]
+    pub mint_base: InterfaceAccount<'info, Mint>,
+    #[account( constraint = mint_quote.key() == data.mint_quote)]
+    pub mint_quote: InterfaceAccount<'info, Mint>,
+    // A buyer who received the option by transfer may not hold the mint yet
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        associated_token::mint = mint_base,
+        associated_token::authority = buyer,
+        associated_token::token_program = token_program,
+    )]
+    pub ata_buyer_base: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = ata_buyer_quote.amount >= data.amount_quote,
+        associated_token::mint = data.mint_quote,
+        associated_token::authority = buyer,
+        associated_token::token_program = token_program_quote,
+    )]
+    pub ata_buyer_quote: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint_base,
+        associated_token::authority = data,
+        associated_token::token_program = token_program,
+    )]
+    pub ata_vault_base: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        associated_token::mint = mint_quote,
+        associated_token::authority = data,
+        associated_token::token_program = token_program_quote,
+    )]
+    pub ata_vault_quote: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        seeds = ["config".as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub token_program_quote: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_exercise_physical<'info>(
+    ctx: Context<'_, '_, '_, 'info, ExercisePhysical<'info>>,
+) -> Result<()> {
+    let clock = Clock::get()?;
+
+    require!(
+        !ctx.accounts.config.is_paused(PAUSE_EXERCISE),
+        ErrorCode::ProtocolPaused
+    );
+
+    require!(
+        clock.unix_timestamp >= ctx.accounts.data.timestamp_expiry,
+        ErrorCode::OptionNotExpired
+    );
+
+    // Exercise closes with the mark grace period, after which the seller can take back the base
+    let deadline = ctx
+        .accounts
+        .data
+        .timestamp_expiry
+        .saturating_add(MARK_GRACE_PERIOD);
+    require!(clock.unix_timestamp < deadline, ErrorCode::OptionExpired);
+
+    require!(
+        ctx.accounts.data.settlement == SettlementMode::Physical,
+        ErrorCode::InvalidSettlementMode
+    );
+
+    require!(
+        ctx.accounts.data.amount_premium.is_some(),
+        ErrorCode::OptionNotPurchased
+    );
+
+    require!(
+        !ctx.accounts.data.is_exercised,
+        ErrorCode::OptionAlreadyExercised
+    );
+
+    // Transfer quote from buyer to vault, collected by the seller on close. The buyer
+    // covers any transfer fee so the seller receives all of amount_quote
+    let amount_quote = get_amount_with_transfer_fee(
+        &ctx.accounts.mint_quote.to_account_info(),
+        ctx.accounts.data.amount_quote,
+    )?;
+    transfer_checked(
+        CpiContext::new(
+            ctx.accounts.token_program_quote.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.ata_buyer_quote.to_account_info(),
+                to: ctx.accounts.ata_vault_quote.to_account_info(),
+                mint: ctx.accounts.mint_quote.to_account_info(),
+                authority: ctx.accounts.buyer.to_account_info(),
+            },
+        )
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+        amount_quote,
+        ctx.accounts.mint_quote.decimals,
+    )?;
+
+    let seeds = [
+        "covered-call".as_bytes(),
+        ctx.accounts.data.creator.as_ref(),
+        &ctx.accounts.data.nonce.to_le_bytes(),
+        &[ctx.accounts.data.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    // Transfer all of base from vault to buyer
+    transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.ata_vault_base.to_account_info(),
+                to: ctx.accounts.ata_buyer_base.to_account_info(),
+                mint: ctx.accounts.mint_base.to_account_info(),
+                authority: ctx.accounts.data.to_account_info(),
+            },
+            signer,
+        )
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+        ctx.accounts.data.amount_base,
+        ctx.accounts.mint_base.decimals,
+    )?;
+
+    ctx.accounts.data.is_exercised = true;
+
+    emit_cpi!(OptionExercised {
+        option: ctx.accounts.data.key(),
+        buyer: ctx.accounts.buyer.key(),
+        settlement: SettlementMode::Physical,
+        strike: calc_strike(
+            ctx.accounts.data.amount_base,
+            ctx.accounts.data.amount_quote,
+            ctx.accounts.data.decimals_base,
+            ctx.accounts.data.decimals_quote,
+            MIN_PRICE_EXPONENT,
+        )?,
+        exponent: MIN_PRICE_EXPONENT,
+        mark: None,
+        amount_seller: ctx.accounts.data.amount_quote,
+        amount_buyer: ctx.accounts.data.amount_base,
+        fee: 0,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,191 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{burn, Burn, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::math::{calc_bps, calc_strike, get_buyer_settlement};
+use crate::state::{Config, OptionSeries};
+use crate::token::pay_out_with_fee;
+use crate::{error::ErrorCode, events::SeriesExercised, ExpiryData, PAUSE_EXERCISE};
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(amount: u64)]
+pub struct ExerciseSeries<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    #[account(
+        seeds = [
+            "option-series".as_bytes(),
+            series.mint_base.as_ref(),
+            series.mint_quote.as_ref(),
+            &series.feed_id,
+            &series.timestamp_expiry.to_le_bytes(),
+            &series.amount_base.to_le_bytes(),
+            &series.amount_quote.to_le_bytes(),
+            &[series.price_kind as u8],
+        ],
+        bump = series.bump,
+    )]
+    pub series: Account<'info, OptionSeries>,
+    #[account(
+        seeds = [
+          "expiry-meta".as_bytes(),
+          &series.feed_id,
+          &series.timestamp_expiry.to_le_bytes(),
+        ],
+        bump = expiry.bump,
+    )]
+    pub expiry: Account<'info, ExpiryData>,
+    #[account( constraint = mint_base.key() == series.mint_base)]
+    pub mint_base: InterfaceAccount<'info, Mint>,
+    #[account(mut, constraint = mint_option.key() == series.mint_option)]
+    pub mint_option: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        constraint = ata_buyer_option.amount >= amount,
+        associated_token::mint = mint_option,
+        associated_token::authority = buyer,
+        associated_token::token_program = token_program,
+    )]
+    pub ata_buyer_option: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        associated_token::mint = mint_base,
+        associated_token::authority = buyer,
+        associated_token::token_program = token_program,
+    )]
+    pub ata_buyer_base: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint_base,
+        associated_token::authority = series,
+        associated_token::token_program = token_program,
+    )]
+    pub ata_vault_base: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        seeds = ["config".as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+    // Created with the market, only needed when a fee is charged
+    #[account(
+        mut,
+        seeds = [b"fee-vault", mint_base.key().as_ref()],
+        bump,
+        token::mint = mint_base,
+        token::authority = config,
+        token::token_program = token_program,
+    )]
+    pub fee_vault_base: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_exercise_series<'info>(
+    ctx: Context<'_, '_, '_, 'info, ExerciseSeries<'info>>,
+    amount: u64,
+) -> Result<()> {
+    let clock = Clock::get()?;
+
+    require!(
+        !ctx.accounts.config.is_paused(PAUSE_EXERCISE),
+        ErrorCode::ProtocolPaused
+    );
+
+    require!(amount > 0, ErrorCode::AmountTooSmall);
+
+    require!(
+        clock.unix_timestamp >= ctx.accounts.series.timestamp_expiry,
+        ErrorCode::OptionNotExpired
+    );
+    // Incase expiry account was initialized but not set
+    require!(ctx.accounts.expiry.price != 0, ErrorCode::OptionNotMarked);
+    // Holders settle one by one, so they must all get the same price
+    let mark = ctx
+        .accounts
+        .expiry
+        .final_settlement_price(
+            ctx.accounts.series.price_kind,
+            ctx.accounts.series.timestamp_expiry,
+            clock.unix_timestamp,
+        )
+        .ok_or(ErrorCode::MarkNotFinal)?;
+
+    let strike = calc_strike(
+        ctx.accounts.series.amount_base,
+        ctx.accounts.series.amount_quote,
+        ctx.accounts.series.decimals_base,
+        ctx.accounts.series.decimals_quote,
+        ctx.accounts.expiry.exponent,
+    )?;
+
+    let amount_itm = get_buyer_settlement(strike, mark, amount)?;
+
+    let fee = calc_bps(amount_itm, ctx.accounts.config.params.fee_settlement_bps)?;
+    let amount_buyer = amount_itm - fee;
+
+    // Burn the option tokens being exercised, out of the money ones are simply retired
+    burn(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.mint_option.to_account_info(),
+                from: ctx.accounts.ata_buyer_option.to_account_info(),
+                authority: ctx.accounts.buyer.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    let seeds = [
+        "option-series".as_bytes(),
+        ctx.accounts.series.mint_base.as_ref(),
+        ctx.accounts.series.mint_quote.as_ref(),
+        &ctx.accounts.series.feed_id,
+        &ctx.accounts.series.timestamp_expiry.to_le_bytes(),
+        &ctx.accounts.series.amount_base.to_le_bytes(),
+        &ctx.accounts.series.amount_quote.to_le_bytes(),
+        &[ctx.accounts.series.price_kind as u8],
+        &[ctx.accounts.series.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    // Transfer base from vault to buyer, and the protocol fee
+    pay_out_with_fee(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.ata_vault_base.to_account_info(),
+                to: ctx.accounts.ata_buyer_base.to_account_info(),
+                mint: ctx.accounts.mint_base.to_account_info(),
+                authority: ctx.accounts.series.to_account_info(),
+            },
+            signer,
+        )
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+        ctx.accounts
+            .fee_vault_base
+            .as_ref()
+            .map(|x| x.to_account_info()),
+        amount_buyer,
+        fee,
+        ctx.accounts.mint_base.decimals,
+    )?;
+
+    emit_cpi!(SeriesExercised {
+        series: ctx.accounts.series.key(),
+        buyer: ctx.accounts.buyer.key(),
+        strike,
+        exponent: ctx.accounts.expiry.exponent,
+        mark,
+        amount,
+        amount_buyer,
+        fee,
+    });
+
+    Ok(())
+}
@@ -1,18 +1,30 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{transfer_checked, Mint, Token, TokenAccount, TransferChecked},
+    token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked},
 };
 
 use crate::error::ErrorCode;
-use crate::state::CoveredCall;
+use crate::events::{OptionCreated, OptionKind};
+use crate::math::calc_strike;
+use crate::state::{Config, CoveredCall, ExerciseStyle, MarketConfig, PriceKind, SettlementMode};
+use crate::token::{check_mint, get_amount_with_transfer_fee, transfer_checked};
+use crate::{MARK_GRACE_PERIOD, MAX_EXERCISE_DATES, MIN_PRICE_EXPONENT, PAUSE_INITIALIZE};
 
+#[event_cpi]
 #[derive(Accounts)]
-#[instruction(amount_base: u64, amount_quote: u64, timestamp_expiry: i64)]
+#[instruction(
+    amount_base: u64,
+    amount_quote: u64,
+    timestamp_expiry: i64,
+    amount_premium_ask: u64,
+    nonce: u64,
+)]
 pub struct Initialize<'info> {
     #[account(mut)]
     pub seller: Signer<'info>,
-    pub buyer: SystemAccount<'info>,
+    // Leave empty to post an open offer that anyone can buy
+    pub buyer: Option<SystemAccount<'info>>,
     #[account(
         init,
         payer = seller,
@@ -20,66 +32,171 @@ pub struct Initialize<'info> {
         seeds = [
             b"covered-call",
             seller.key().as_ref(),
-            buyer.key().as_ref(),
-            mint_base.key().as_ref(),
-            mint_quote.key().as_ref(),
-            amount_base.to_le_bytes().as_ref(),
-            amount_quote.to_le_bytes().as_ref(),
-            timestamp_expiry.to_le_bytes().as_ref(),
+            nonce.to_le_bytes().as_ref(),
         ],
         bump,
     )]
     pub data: Account<'info, CoveredCall>,
-    pub mint_base: Account<'info, Mint>,
-    pub mint_quote: Account<'info, Mint>,
+    pub mint_base: InterfaceAccount<'info, Mint>,
+    pub mint_quote: InterfaceAccount<'info, Mint>,
+    pub mint_premium: InterfaceAccount<'info, Mint>,
     #[account(
         mut,
         constraint = ata_seller_base.amount >= amount_base,
         associated_token::mint = mint_base,
         associated_token::authority = seller,
+        associated_token::token_program = token_program,
     )]
-    pub ata_seller_base: Account<'info, TokenAccount>,
+    pub ata_seller_base: InterfaceAccount<'info, TokenAccount>,
     #[account(
         init,
         payer = seller,
         associated_token::mint = mint_base,
         associated_token::authority = data,
+        associated_token::token_program = token_program,
+    )]
+    pub ata_vault_base: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        seeds = ["config".as_bytes()],
+        bump = config.bump,
     )]
-    pub ata_vault_base: Account<'info, TokenAccount>,
+    pub config: Account<'info, Config>,
+    #[account(
+        seeds = [
+            "market".as_bytes(),
+            mint_base.key().as_ref(),
+            mint_quote.key().as_ref(),
+        ],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, MarketConfig>,
+    // Markets only create vaults for their pair, so the premium mint may not have one yet
+    #[account(
+        init_if_needed,
+        payer = seller,
+        seeds = [b"fee-vault", mint_premium.key().as_ref()],
+        bump,
+        token::mint = mint_premium,
+        token::authority = config,
+        token::token_program = token_program_premium,
+    )]
+    pub fee_vault_premium: InterfaceAccount<'info, TokenAccount>,
     pub associated_token_program: Program<'info, AssociatedToken>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub token_program_premium: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
 
-pub fn handle_initialize(
-    ctx: Context<Initialize>,
+#[allow(clippy::too_many_arguments)]
+pub fn handle_initialize<'info>(
+    ctx: Context<'_, '_, '_, 'info, Initialize<'info>>,
     amount_base: u64,
     amount_quote: u64,
     timestamp_expiry: i64,
+    amount_premium_ask: u64,
+    nonce: u64,
+    settlement: SettlementMode,
+    style: ExerciseStyle,
+    exercise_dates: Vec<i64>,
+    price_kind: PriceKind,
 ) -> Result<()> {
     let clock = Clock::get()?;
 
+    require!(
+        !ctx.accounts.config.is_paused(PAUSE_INITIALIZE),
+        ErrorCode::ProtocolPaused
+    );
+
     require!(
         timestamp_expiry > clock.unix_timestamp,
         ErrorCode::ExpiryIsInThePast
     );
 
+    ctx.accounts
+        .market
+        .check_option(amount_base, timestamp_expiry, clock.unix_timestamp)?;
+    let feed_id = ctx.accounts.market.feed_id;
+
+    for mint in [
+        &ctx.accounts.mint_base,
+        &ctx.accounts.mint_quote,
+        &ctx.accounts.mint_premium,
+    ] {
+        check_mint(&mint.to_account_info(), &ctx.accounts.config)?;
+    }
+
+    // Ensure strike is representable at any oracle exponent we accept
+    calc_strike(
+        amount_base,
+        amount_quote,
+        ctx.accounts.mint_base.decimals,
+        ctx.accounts.mint_quote.decimals,
+        MIN_PRICE_EXPONENT,
+    )?;
+
+    // Settling before expiry is only supported in cash
+    require!(
+        style == ExerciseStyle::European || settlement == SettlementMode::Cash,
+        ErrorCode::InvalidSettlementMode
+    );
+
+    // Exercising before expiry settles on a live price, not on an average of the window
+    require!(
+        style == ExerciseStyle::European || price_kind == PriceKind::Spot,
+        ErrorCode::InvalidPriceKind
+    );
+
+    require!(
+        (style == ExerciseStyle::Bermudan) != exercise_dates.is_empty(),
+        ErrorCode::InvalidExerciseStyle
+    );
+
+    // Dates must be ascending and fall strictly between now and expiry, each leaving its mark
+    // time to become final before the next date supersedes it
+    require!(
+        exercise_dates.len() <= MAX_EXERCISE_DATES
+            && exercise_dates
+                .windows(2)
+                .all(|x| x[0].saturating_add(MARK_GRACE_PERIOD) < x[1])
+            && exercise_dates.iter().all(|x| {
+                clock.unix_timestamp < *x && x.saturating_add(MARK_GRACE_PERIOD) < timestamp_expiry
+            }),
+        ErrorCode::InvalidExerciseDate
+    );
+
     // Set state
     ctx.accounts.data.set_inner(CoveredCall {
         amount_base,
         amount_premium: None,
+        amount_premium_ask,
         amount_quote,
         bump: ctx.bumps.data,
-        buyer: ctx.accounts.buyer.key(),
+        buyer: ctx
+            .accounts
+            .buyer
+            .as_ref()
+            .map_or(Pubkey::default(), |x| x.key()),
+        creator: ctx.accounts.seller.key(),
+        decimals_base: ctx.accounts.mint_base.decimals,
+        decimals_quote: ctx.accounts.mint_quote.decimals,
+        exercise_dates,
+        feed_id,
         is_exercised: false,
         mint_base: ctx.accounts.mint_base.key(),
+        mint_premium: ctx.accounts.mint_premium.key(),
         mint_quote: ctx.accounts.mint_quote.key(),
+        nonce,
+        price_kind,
         seller: ctx.accounts.seller.key(),
+        settlement,
+        style,
         timestamp_created: clock.unix_timestamp,
         timestamp_expiry,
     });
 
-    // Transfer base to vault
+    // Transfer base to vault, topped up by any transfer fee so it holds all of amount_base
+    let amount =
+        get_amount_with_transfer_fee(&ctx.accounts.mint_base.to_account_info(), amount_base)?;
     transfer_checked(
         CpiContext::new(
             ctx.accounts.token_program.to_account_info(),
@@ -89,10 +206,30 @@ pub fn handle_initialize(
                 mint: ctx.accounts.mint_base.to_account_info(),
                 authority: ctx.accounts.seller.to_account_info(),
             },
-        ),
-        amount_base,
+        )
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+        amount,
         ctx.accounts.mint_base.decimals,
     )?;
 
+    emit_cpi!(OptionCreated {
+        option: ctx.accounts.data.key(),
+        kind: OptionKind::CoveredCall,
+        seller: ctx.accounts.data.seller,
+        buyer: ctx.accounts.data.buyer,
+        mint_base: ctx.accounts.data.mint_base,
+        mint_quote: ctx.accounts.data.mint_quote,
+        mint_premium: ctx.accounts.data.mint_premium,
+        amount_base,
+        amount_quote,
+        amount_premium_ask,
+        timestamp_expiry,
+        settlement,
+        style,
+        exercise_dates: ctx.accounts.data.exercise_dates.clone(),
+        price_kind,
+        feed_id,
+    });
+
     Ok(())
 }
@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+
+use crate::events::ConfigUpdated;
+use crate::state::{Config, ConfigParams};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    // Becomes the admin, only the upgrade authority can claim it
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [crate::ID.as_ref()],
+        bump,
+        seeds::program = bpf_loader_upgradeable::ID,
+        constraint = program_data.upgrade_authority_address == Some(admin.key()),
+    )]
+    pub program_data: Account<'info, ProgramData>,
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Config::INIT_SPACE,
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_initialize_config(
+    ctx: Context<InitializeConfig>,
+    params: ConfigParams,
+) -> Result<()> {
+    params.validate()?;
+
+    ctx.accounts.config.set_inner(Config {
+        admin: ctx.accounts.admin.key(),
+        bump: ctx.bumps.config,
+        paused: 0,
+        params: params.clone(),
+    });
+
+    emit_cpi!(ConfigUpdated {
+        config: ctx.accounts.config.key(),
+        admin: ctx.accounts.admin.key(),
+        params,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,82 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::events::MarketUpdated;
+use crate::state::{Config, MarketConfig, MarketParams};
+use crate::token::check_mint;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitializeMarket<'info> {
+    #[account(mut, constraint = admin.key() == config.admin)]
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = ["config".as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+    pub mint_base: InterfaceAccount<'info, Mint>,
+    pub mint_quote: InterfaceAccount<'info, Mint>,
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + MarketConfig::INIT_SPACE,
+        seeds = [b"market", mint_base.key().as_ref(), mint_quote.key().as_ref()],
+        bump,
+    )]
+    pub market: Account<'info, MarketConfig>,
+    // Fee vaults of the pair, shared with other markets on the same mints
+    #[account(
+        init_if_needed,
+        payer = admin,
+        seeds = [b"fee-vault", mint_base.key().as_ref()],
+        bump,
+        token::mint = mint_base,
+        token::authority = config,
+        token::token_program = token_program,
+    )]
+    pub fee_vault_base: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = admin,
+        seeds = [b"fee-vault", mint_quote.key().as_ref()],
+        bump,
+        token::mint = mint_quote,
+        token::authority = config,
+        token::token_program = token_program_quote,
+    )]
+    pub fee_vault_quote: InterfaceAccount<'info, TokenAccount>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub token_program_quote: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_initialize_market(
+    ctx: Context<InitializeMarket>,
+    feed_id: [u8; 32],
+    params: MarketParams,
+) -> Result<()> {
+    params.validate()?;
+
+    for mint in [&ctx.accounts.mint_base, &ctx.accounts.mint_quote] {
+        check_mint(&mint.to_account_info(), &ctx.accounts.config)?;
+    }
+
+    ctx.accounts.market.set_inner(MarketConfig {
+        bump: ctx.bumps.market,
+        mint_base: ctx.accounts.mint_base.key(),
+        mint_quote: ctx.accounts.mint_quote.key(),
+        feed_id,
+        params: params.clone(),
+    });
+
+    emit_cpi!(MarketUpdated {
+        market: ctx.accounts.market.key(),
+        mint_base: ctx.accounts.market.mint_base,
+        mint_quote: ctx.accounts.market.mint_quote,
+        feed_id,
+        params,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,198 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::error::ErrorCode;
+use crate::events::{OptionCreated, OptionKind};
+use crate::math::calc_strike;
+use crate::state::{
+    CashSecuredPut, Config, ExerciseStyle, MarketConfig, PriceKind, SettlementMode,
+};
+use crate::token::{check_mint, get_amount_with_transfer_fee, transfer_checked};
+use crate::{MIN_PRICE_EXPONENT, PAUSE_INITIALIZE};
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(
+    amount_base: u64,
+    amount_quote: u64,
+    timestamp_expiry: i64,
+    amount_premium_ask: u64,
+    nonce: u64,
+)]
+pub struct InitializePut<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+    // Leave empty to post an open offer that anyone can buy
+    pub buyer: Option<SystemAccount<'info>>,
+    #[account(
+        init,
+        payer = seller,
+        space = 8 + CashSecuredPut::INIT_SPACE,
+        seeds = [
+            b"cash-secured-put",
+            seller.key().as_ref(),
+            nonce.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub data: Account<'info, CashSecuredPut>,
+    pub mint_base: InterfaceAccount<'info, Mint>,
+    pub mint_quote: InterfaceAccount<'info, Mint>,
+    pub mint_premium: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        constraint = ata_seller_quote.amount >= amount_quote,
+        associated_token::mint = mint_quote,
+        associated_token::authority = seller,
+        associated_token::token_program = token_program,
+    )]
+    pub ata_seller_quote: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = seller,
+        associated_token::mint = mint_quote,
+        associated_token::authority = data,
+        associated_token::token_program = token_program,
+    )]
+    pub ata_vault_quote: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        seeds = ["config".as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        seeds = [
+            "market".as_bytes(),
+            mint_base.key().as_ref(),
+            mint_quote.key().as_ref(),
+        ],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, MarketConfig>,
+    // Markets only create vaults for their pair, so the premium mint may not have one yet
+    #[account(
+        init_if_needed,
+        payer = seller,
+        seeds = [b"fee-vault", mint_premium.key().as_ref()],
+        bump,
+        token::mint = mint_premium,
+        token::authority = config,
+        token::token_program = token_program_premium,
+    )]
+    pub fee_vault_premium: InterfaceAccount<'info, TokenAccount>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub token_program_premium: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_initialize_put<'info>(
+    ctx: Context<'_, '_, '_, 'info, InitializePut<'info>>,
+    amount_base: u64,
+    amount_quote: u64,
+    timestamp_expiry: i64,
+    amount_premium_ask: u64,
+    nonce: u64,
+) -> Result<()> {
+    let clock = Clock::get()?;
+
+    require!(
+        !ctx.accounts.config.is_paused(PAUSE_INITIALIZE),
+        ErrorCode::ProtocolPaused
+    );
+
+    require!(
+        timestamp_expiry > clock.unix_timestamp,
+        ErrorCode::ExpiryIsInThePast
+    );
+
+    ctx.accounts
+        .market
+        .check_option(amount_base, timestamp_expiry, clock.unix_timestamp)?;
+    let feed_id = ctx.accounts.market.feed_id;
+
+    for mint in [
+        &ctx.accounts.mint_base,
+        &ctx.accounts.mint_quote,
+        &ctx.accounts.mint_premium,
+    ] {
+        check_mint(&mint.to_account_info(), &ctx.accounts.config)?;
+    }
+
+    // Ensure strike is representable at any oracle exponent we accept
+    calc_strike(
+        amount_base,
+        amount_quote,
+        ctx.accounts.mint_base.decimals,
+        ctx.accounts.mint_quote.decimals,
+        MIN_PRICE_EXPONENT,
+    )?;
+
+    // Set state
+    ctx.accounts.data.set_inner(CashSecuredPut {
+        amount_base,
+        amount_premium: None,
+        amount_premium_ask,
+        amount_quote,
+        bump: ctx.bumps.data,
+        buyer: ctx
+            .accounts
+            .buyer
+            .as_ref()
+            .map_or(Pubkey::default(), |x| x.key()),
+        creator: ctx.accounts.seller.key(),
+        decimals_base: ctx.accounts.mint_base.decimals,
+        decimals_quote: ctx.accounts.mint_quote.decimals,
+        feed_id,
+        is_exercised: false,
+        mint_base: ctx.accounts.mint_base.key(),
+        mint_premium: ctx.accounts.mint_premium.key(),
+        mint_quote: ctx.accounts.mint_quote.key(),
+        nonce,
+        seller: ctx.accounts.seller.key(),
+        timestamp_created: clock.unix_timestamp,
+        timestamp_expiry,
+    });
+
+    // Transfer quote to vault, topped up by any transfer fee so it holds all of amount_quote
+    let amount =
+        get_amount_with_transfer_fee(&ctx.accounts.mint_quote.to_account_info(), amount_quote)?;
+    transfer_checked(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.ata_seller_quote.to_account_info(),
+                to: ctx.accounts.ata_vault_quote.to_account_info(),
+                mint: ctx.accounts.mint_quote.to_account_info(),
+                authority: ctx.accounts.seller.to_account_info(),
+            },
+        )
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+        amount,
+        ctx.accounts.mint_quote.decimals,
+    )?;
+
+    emit_cpi!(OptionCreated {
+        option: ctx.accounts.data.key(),
+        kind: OptionKind::CashSecuredPut,
+        seller: ctx.accounts.data.seller,
+        buyer: ctx.accounts.data.buyer,
+        mint_base: ctx.accounts.data.mint_base,
+        mint_quote: ctx.accounts.data.mint_quote,
+        mint_premium: ctx.accounts.data.mint_premium,
+        amount_base,
+        amount_quote,
+        amount_premium_ask,
+        timestamp_expiry,
+        settlement: SettlementMode::Cash,
+        style: ExerciseStyle::European,
+        exercise_dates: vec![],
+        price_kind: PriceKind::Spot,
+        feed_id,
+    });
+
+    Ok(())
+}
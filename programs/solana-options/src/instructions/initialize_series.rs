@@ -0,0 +1,153 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::error::ErrorCode;
+use crate::events::SeriesCreated;
+use crate::math::calc_strike;
+use crate::state::{Config, MarketConfig, OptionSeries, PriceKind};
+use crate::token::check_mint;
+use crate::{MIN_PRICE_EXPONENT, PAUSE_INITIALIZE};
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(amount_base: u64, amount_quote: u64, timestamp_expiry: i64, price_kind: PriceKind)]
+pub struct InitializeSeries<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [
+            "market".as_bytes(),
+            mint_base.key().as_ref(),
+            mint_quote.key().as_ref(),
+        ],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, MarketConfig>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + OptionSeries::INIT_SPACE,
+        seeds = [
+            b"option-series",
+            mint_base.key().as_ref(),
+            mint_quote.key().as_ref(),
+            market.feed_id.as_ref(),
+            &timestamp_expiry.to_le_bytes(),
+            &amount_base.to_le_bytes(),
+            &amount_quote.to_le_bytes(),
+            &[price_kind as u8],
+        ],
+        bump,
+    )]
+    pub series: Account<'info, OptionSeries>,
+    pub mint_base: InterfaceAccount<'info, Mint>,
+    pub mint_quote: InterfaceAccount<'info, Mint>,
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"option-mint", series.key().as_ref()],
+        bump,
+        mint::decimals = mint_base.decimals,
+        mint::authority = series,
+        mint::token_program = token_program,
+    )]
+    pub mint_option: InterfaceAccount<'info, Mint>,
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"writer-mint", series.key().as_ref()],
+        bump,
+        mint::decimals = mint_base.decimals,
+        mint::authority = series,
+        mint::token_program = token_program,
+    )]
+    pub mint_writer: InterfaceAccount<'info, Mint>,
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = mint_base,
+        associated_token::authority = series,
+        associated_token::token_program = token_program,
+    )]
+    pub ata_vault_base: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        seeds = ["config".as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_initialize_series(
+    ctx: Context<InitializeSeries>,
+    amount_base: u64,
+    amount_quote: u64,
+    timestamp_expiry: i64,
+    price_kind: PriceKind,
+) -> Result<()> {
+    let clock = Clock::get()?;
+
+    require!(
+        !ctx.accounts.config.is_paused(PAUSE_INITIALIZE),
+        ErrorCode::ProtocolPaused
+    );
+
+    require!(
+        timestamp_expiry > clock.unix_timestamp,
+        ErrorCode::ExpiryIsInThePast
+    );
+
+    // The minimum size is checked on each write, amount_base only sets the strike
+    ctx.accounts
+        .market
+        .check_expiry(timestamp_expiry, clock.unix_timestamp)?;
+    let feed_id = ctx.accounts.market.feed_id;
+
+    for mint in [&ctx.accounts.mint_base, &ctx.accounts.mint_quote] {
+        check_mint(&mint.to_account_info(), &ctx.accounts.config)?;
+    }
+
+    // Ensure strike is representable at any oracle exponent we accept
+    calc_strike(
+        amount_base,
+        amount_quote,
+        ctx.accounts.mint_base.decimals,
+        ctx.accounts.mint_quote.decimals,
+        MIN_PRICE_EXPONENT,
+    )?;
+
+    ctx.accounts.series.set_inner(OptionSeries {
+        amount_base,
+        amount_quote,
+        bump: ctx.bumps.series,
+        decimals_base: ctx.accounts.mint_base.decimals,
+        decimals_quote: ctx.accounts.mint_quote.decimals,
+        feed_id,
+        mint_base: ctx.accounts.mint_base.key(),
+        mint_option: ctx.accounts.mint_option.key(),
+        mint_quote: ctx.accounts.mint_quote.key(),
+        mint_writer: ctx.accounts.mint_writer.key(),
+        timestamp_expiry,
+        price_kind,
+    });
+
+    emit_cpi!(SeriesCreated {
+        series: ctx.accounts.series.key(),
+        mint_base: ctx.accounts.series.mint_base,
+        mint_quote: ctx.accounts.series.mint_quote,
+        mint_option: ctx.accounts.series.mint_option,
+        mint_writer: ctx.accounts.series.mint_writer,
+        amount_base,
+        amount_quote,
+        timestamp_expiry,
+        feed_id,
+        price_kind,
+    });
+
+    Ok(())
+}
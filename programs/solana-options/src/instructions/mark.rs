@@ -1,14 +1,29 @@
 use anchor_lang::prelude::*;
 
 use crate::error::ErrorCode;
-use crate::ExpiryData;
-use pyth_solana_receiver_sdk::price_update::{get_feed_id_from_hex, PriceUpdateV2};
+use crate::events::ExpiryMarked;
+use crate::{
+    Config, ExpiryData, MarketConfig, MARK_GRACE_PERIOD, MIN_PRICE_EXPONENT, MIN_SAMPLE_SPACING,
+    PAUSE_MARK,
+};
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
 
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(timestamp_expiry: i64)]
 pub struct Mark<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
+    // Any market on the feed can be used, marks are shared per feed
+    #[account(
+        seeds = [
+            "market".as_bytes(),
+            market.mint_base.as_ref(),
+            market.mint_quote.as_ref(),
+        ],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, MarketConfig>,
 
     #[account(
       init_if_needed,
@@ -16,39 +31,66 @@ pub struct Mark<'info> {
       space = 8 + ExpiryData::INIT_SPACE,
       seeds = [
           "expiry-meta".as_bytes(),
+          market.feed_id.as_ref(),
           timestamp_expiry.to_le_bytes().as_ref(),
       ],
       bump,
   )]
     pub expiry: Account<'info, ExpiryData>,
     pub price_update: Account<'info, PriceUpdateV2>,
+    #[account(
+        seeds = ["config".as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
     pub system_program: Program<'info, System>,
 }
 
 pub fn handle_mark(ctx: Context<Mark>, expiry: i64) -> Result<()> {
+    let feed_id = ctx.accounts.market.feed_id;
     let price_update = &mut ctx.accounts.price_update;
 
-    let window: i64 = 30 * 60; // Allow prices in this time before expiry
+    let window = ctx.accounts.config.params.mark_window; // Allow prices in this time before expiry
     let clock = Clock::get()?;
 
-    let maximum_age: u64 = (clock.unix_timestamp - (expiry - window))
-        .try_into()
-        .unwrap_or_else(|_| window.try_into().unwrap());
+    require!(
+        !ctx.accounts.config.is_paused(PAUSE_MARK),
+        ErrorCode::ProtocolPaused
+    );
 
-    let feed_id: [u8; 32] =
-        get_feed_id_from_hex("0xef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d")?;
+    let window_start = expiry.checked_sub(window).ok_or(ErrorCode::MathOverflow)?;
+    // Any price since the window opened, or the window's length while it hasn't opened yet
+    let maximum_age = clock
+        .unix_timestamp
+        .checked_sub(window_start)
+        .ok_or(ErrorCode::MathOverflow)?;
+    let maximum_age = u64::try_from(maximum_age).unwrap_or(window.unsigned_abs());
 
     let price = price_update.get_price_no_older_than(&clock, maximum_age, &feed_id)?;
 
+    require!(!ctx.accounts.expiry.is_finalized, ErrorCode::MarkFinalized);
+
     require!(
-        (expiry - window) < price.publish_time && price.publish_time <= expiry,
+        window_start < price.publish_time && price.publish_time <= expiry,
         ErrorCode::PriceIrrelevant,
     );
 
-    // Ensure updated price is more recent
     require!(
-        price.publish_time >= ctx.accounts.expiry.publish_time,
-        ErrorCode::PriceIrrelevant
+        price.price > 0 && price.exponent >= MIN_PRICE_EXPONENT,
+        ErrorCode::InvalidOraclePrice
+    );
+
+    // Reject prices the oracle is not confident enough in to settle on
+    require!(
+        u128::from(price.conf) * 10_000
+            <= u128::from(price.price.unsigned_abs())
+                * u128::from(ctx.accounts.config.params.max_conf_bps),
+        ErrorCode::ConfidenceTooWide
+    );
+
+    require!(
+        ctx.accounts.expiry.price == 0 || price.exponent == ctx.accounts.expiry.exponent,
+        ErrorCode::InvalidOraclePrice
     );
 
     // Set payer for rent repayment if none set
@@ -58,13 +100,51 @@ pub fn handle_mark(ctx: Context<Mark>, expiry: i64) -> Result<()> {
         ctx.accounts.expiry.payer
     };
 
-    ctx.accounts.expiry.set_inner(ExpiryData {
+    let expiry_data = &mut ctx.accounts.expiry;
+
+    // The spot price only moves to more recent prices, and is fixed after the grace period
+    // unless nobody marked it in time
+    let is_spot = price.publish_time >= expiry_data.publish_time
+        && (expiry_data.price == 0
+            || clock.unix_timestamp < expiry.saturating_add(MARK_GRACE_PERIOD));
+
+    // Samples fill their slot of the window in any order, until finalized. The window check above
+    // keeps the slot below mark_window / MIN_SAMPLE_SPACING, which fits the bitmap
+    let bucket = 1u128 << ((expiry - price.publish_time) / MIN_SAMPLE_SPACING);
+    let is_sample = expiry_data.sample_buckets & bucket == 0;
+
+    require!(is_spot || is_sample, ErrorCode::PriceIrrelevant);
+
+    if is_sample {
+        expiry_data.price_sum = expiry_data
+            .price_sum
+            .checked_add(price.price.into())
+            .ok_or(ErrorCode::MathOverflow)?;
+        expiry_data.sample_count += 1;
+        expiry_data.sample_buckets |= bucket;
+    }
+
+    if is_spot {
+        expiry_data.price = price.price;
+        expiry_data.conf = price.conf;
+        expiry_data.publish_time = price.publish_time;
+    }
+    expiry_data.exponent = price.exponent;
+    expiry_data.bump = ctx.bumps.expiry;
+    expiry_data.payer = payer;
+    expiry_data.feed_id = feed_id;
+
+    emit_cpi!(ExpiryMarked {
+        expiry: ctx.accounts.expiry.key(),
+        feed_id,
+        timestamp_expiry: expiry,
         price: price.price,
         conf: price.conf,
         exponent: price.exponent,
         publish_time: price.publish_time,
-        bump: ctx.bumps.expiry,
         payer,
+        sample_count: ctx.accounts.expiry.sample_count,
     });
+
     Ok(())
 }
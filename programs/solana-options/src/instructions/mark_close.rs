@@ -1,16 +1,22 @@
 use anchor_lang::prelude::*;
 
-use crate::ExpiryData;
+use crate::{
+    error::ErrorCode, events::ExpiryMarkClosed, ExpiryData, MARK_GRACE_PERIOD,
+    MARK_RETENTION_PERIOD,
+};
 
+#[event_cpi]
 #[derive(Accounts)]
-#[instruction(timestamp_expiry: i64)]
+#[instruction(timestamp_expiry: i64, feed_id: [u8; 32])]
 pub struct MarkClose<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
     #[account(
       mut,
+      constraint = payer.key() == expiry.payer,
       seeds = [
           "expiry-meta".as_bytes(),
+          feed_id.as_ref(),
           timestamp_expiry.to_le_bytes().as_ref(),
       ],
       bump = expiry.bump,
@@ -20,10 +26,24 @@ pub struct MarkClose<'info> {
     pub system_program: Program<'info, System>,
 }
 
-pub fn handle_mark_close(ctx: Context<MarkClose>, _expiry: i64) -> Result<()> {
+pub fn handle_mark_close(ctx: Context<MarkClose>, expiry: i64, feed_id: [u8; 32]) -> Result<()> {
+    // Options settle on the mark from expiry on, so it is kept until it is final and holders
+    // have had the retention period to settle, including finalizing an average
+    let now = Clock::get()?.unix_timestamp;
+    let retained_until = expiry
+        .saturating_add(MARK_GRACE_PERIOD)
+        .saturating_add(MARK_RETENTION_PERIOD);
     require!(
-        ctx.accounts.payer.key() == ctx.accounts.expiry.payer,
-        ErrorCode::ConstraintOwner,
+        now < expiry || now >= retained_until,
+        ErrorCode::MarkExpired
     );
+
+    emit_cpi!(ExpiryMarkClosed {
+        expiry: ctx.accounts.expiry.key(),
+        feed_id,
+        timestamp_expiry: expiry,
+        payer: ctx.accounts.payer.key(),
+    });
+
     Ok(())
 }
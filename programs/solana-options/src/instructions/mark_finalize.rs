@@ -0,0 +1,72 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+use crate::{
+    events::ExpiryFinalized, Config, ExpiryData, MARK_GRACE_PERIOD, MIN_SAMPLE_SPACING, PAUSE_MARK,
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(timestamp_expiry: i64, feed_id: [u8; 32])]
+pub struct MarkFinalize<'info> {
+    #[account(
+      mut,
+      seeds = [
+          "expiry-meta".as_bytes(),
+          feed_id.as_ref(),
+          timestamp_expiry.to_le_bytes().as_ref(),
+      ],
+      bump = expiry.bump,
+  )]
+    pub expiry: Account<'info, ExpiryData>,
+    #[account(
+        seeds = ["config".as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+// Anyone can fix the average once the grace period after expiry has passed, after which the
+// mark can't change
+pub fn handle_mark_finalize(
+    ctx: Context<MarkFinalize>,
+    expiry: i64,
+    feed_id: [u8; 32],
+) -> Result<()> {
+    let clock = Clock::get()?;
+
+    require!(
+        !ctx.accounts.config.is_paused(PAUSE_MARK),
+        ErrorCode::ProtocolPaused
+    );
+
+    require!(
+        clock.unix_timestamp >= expiry.saturating_add(MARK_GRACE_PERIOD),
+        ErrorCode::MarkNotFinal
+    );
+    require!(!ctx.accounts.expiry.is_finalized, ErrorCode::MarkFinalized);
+    // Samples must cover at least half the slots of the window
+    let min_samples = (ctx.accounts.config.params.mark_window / MIN_SAMPLE_SPACING / 2).max(1);
+    require!(
+        i64::from(ctx.accounts.expiry.sample_count) >= min_samples,
+        ErrorCode::NotEnoughSamples
+    );
+
+    let average_price =
+        ctx.accounts.expiry.price_sum / i128::from(ctx.accounts.expiry.sample_count);
+
+    ctx.accounts.expiry.average_price =
+        i64::try_from(average_price).map_err(|_| ErrorCode::MathOverflow)?;
+    ctx.accounts.expiry.is_finalized = true;
+
+    emit_cpi!(ExpiryFinalized {
+        expiry: ctx.accounts.expiry.key(),
+        feed_id,
+        timestamp_expiry: expiry,
+        average_price: ctx.accounts.expiry.average_price,
+        exponent: ctx.accounts.expiry.exponent,
+        sample_count: ctx.accounts.expiry.sample_count,
+    });
+
+    Ok(())
+}
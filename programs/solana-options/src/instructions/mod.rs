@@ -1,13 +1,63 @@
+pub mod auto_exercise;
+pub mod auto_exercise_put;
 pub mod buy;
+pub mod buy_put;
 pub mod close;
+pub mod close_put;
 pub mod exercise;
+pub mod exercise_early;
+pub mod exercise_physical;
+pub mod exercise_put;
+pub mod exercise_scheduled;
+pub mod exercise_series;
 pub mod initialize;
+pub mod initialize_config;
+pub mod initialize_market;
+pub mod initialize_put;
+pub mod initialize_series;
 pub mod mark;
 pub mod mark_close;
+pub mod mark_finalize;
+pub mod redeem;
+pub mod set_paused;
+pub mod transfer_admin;
+pub mod transfer_buyer;
+pub mod transfer_buyer_put;
+pub mod transfer_seller;
+pub mod transfer_seller_put;
+pub mod update_config;
+pub mod update_market;
+pub mod withdraw_fees;
+pub mod write;
 
+pub use auto_exercise::*;
+pub use auto_exercise_put::*;
 pub use buy::*;
+pub use buy_put::*;
 pub use close::*;
+pub use close_put::*;
 pub use exercise::*;
+pub use exercise_early::*;
+pub use exercise_physical::*;
+pub use exercise_put::*;
+pub use exercise_scheduled::*;
+pub use exercise_series::*;
 pub use initialize::*;
+pub use initialize_config::*;
+pub use initialize_market::*;
+pub use initialize_put::*;
+pub use initialize_series::*;
 pub use mark::*;
 pub use mark_close::*;
+pub use mark_finalize::*;
+pub use redeem::*;
+pub use set_paused::*;
+pub use transfer_admin::*;
+pub use transfer_buyer::*;
+pub use transfer_buyer_put::*;
+pub use transfer_seller::*;
+pub use transfer_seller_put::*;
+pub use update_config::*;
+pub use update_market::*;
+pub use withdraw_fees::*;
+pub use write::*;
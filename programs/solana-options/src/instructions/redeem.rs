@@ -0,0 +1,160 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{burn, Burn, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::math::{calc_strike, get_settlements};
+use crate::state::OptionSeries;
+use crate::token::transfer_checked;
+use crate::{error::ErrorCode, events::SeriesRedeemed, ExpiryData};
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(amount: u64)]
+pub struct Redeem<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+    #[account(
+        seeds = [
+            "option-series".as_bytes(),
+            series.mint_base.as_ref(),
+            series.mint_quote.as_ref(),
+            &series.feed_id,
+            &series.timestamp_expiry.to_le_bytes(),
+            &series.amount_base.to_le_bytes(),
+            &series.amount_quote.to_le_bytes(),
+            &[series.price_kind as u8],
+        ],
+        bump = series.bump,
+    )]
+    pub series: Account<'info, OptionSeries>,
+    #[account(
+        seeds = [
+          "expiry-meta".as_bytes(),
+          &series.feed_id,
+          &series.timestamp_expiry.to_le_bytes(),
+        ],
+        bump = expiry.bump,
+    )]
+    pub expiry: Account<'info, ExpiryData>,
+    #[account( constraint = mint_base.key() == series.mint_base)]
+    pub mint_base: InterfaceAccount<'info, Mint>,
+    #[account(mut, constraint = mint_writer.key() == series.mint_writer)]
+    pub mint_writer: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        constraint = ata_seller_writer.amount >= amount,
+        associated_token::mint = mint_writer,
+        associated_token::authority = seller,
+        associated_token::token_program = token_program,
+    )]
+    pub ata_seller_writer: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = seller,
+        associated_token::mint = mint_base,
+        associated_token::authority = seller,
+        associated_token::token_program = token_program,
+    )]
+    pub ata_seller_base: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint_base,
+        associated_token::authority = series,
+        associated_token::token_program = token_program,
+    )]
+    pub ata_vault_base: InterfaceAccount<'info, TokenAccount>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_redeem<'info>(
+    ctx: Context<'_, '_, '_, 'info, Redeem<'info>>,
+    amount: u64,
+) -> Result<()> {
+    // Never paused, like closing an option, so writers can always get their collateral back
+    let clock = Clock::get()?;
+
+    require!(
+        clock.unix_timestamp >= ctx.accounts.series.timestamp_expiry,
+        ErrorCode::OptionNotExpired
+    );
+    // Incase expiry account was initialized but not set
+    require!(ctx.accounts.expiry.price != 0, ErrorCode::OptionNotMarked);
+    // Holders settle one by one, so they must all get the same price
+    let mark = ctx
+        .accounts
+        .expiry
+        .final_settlement_price(
+            ctx.accounts.series.price_kind,
+            ctx.accounts.series.timestamp_expiry,
+            clock.unix_timestamp,
+        )
+        .ok_or(ErrorCode::MarkNotFinal)?;
+
+    let strike = calc_strike(
+        ctx.accounts.series.amount_base,
+        ctx.accounts.series.amount_quote,
+        ctx.accounts.series.decimals_base,
+        ctx.accounts.series.decimals_quote,
+        ctx.accounts.expiry.exponent,
+    )?;
+
+    let [amount_seller, _] = get_settlements(strike, mark, amount)?;
+
+    burn(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.mint_writer.to_account_info(),
+                from: ctx.accounts.ata_seller_writer.to_account_info(),
+                authority: ctx.accounts.seller.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    let seeds = [
+        "option-series".as_bytes(),
+        ctx.accounts.series.mint_base.as_ref(),
+        ctx.accounts.series.mint_quote.as_ref(),
+        &ctx.accounts.series.feed_id,
+        &ctx.accounts.series.timestamp_expiry.to_le_bytes(),
+        &ctx.accounts.series.amount_base.to_le_bytes(),
+        &ctx.accounts.series.amount_quote.to_le_bytes(),
+        &[ctx.accounts.series.price_kind as u8],
+        &[ctx.accounts.series.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    // Transfer the seller's share of base from vault
+    transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.ata_vault_base.to_account_info(),
+                to: ctx.accounts.ata_seller_base.to_account_info(),
+                mint: ctx.accounts.mint_base.to_account_info(),
+                authority: ctx.accounts.series.to_account_info(),
+            },
+            signer,
+        )
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+        amount_seller,
+        ctx.accounts.mint_base.decimals,
+    )?;
+
+    emit_cpi!(SeriesRedeemed {
+        series: ctx.accounts.series.key(),
+        seller: ctx.accounts.seller.key(),
+        strike,
+        exponent: ctx.accounts.expiry.exponent,
+        mark,
+        amount,
+        amount_seller,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+
+use crate::events::PauseUpdated;
+use crate::state::Config;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetPaused<'info> {
+    #[account( constraint = admin.key() == config.admin)]
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = ["config".as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+// Replaces the whole mask, PAUSE_ALL stops everything and 0 resumes
+pub fn handle_set_paused(ctx: Context<SetPaused>, paused: u32) -> Result<()> {
+    ctx.accounts.config.paused = paused;
+
+    emit_cpi!(PauseUpdated {
+        config: ctx.accounts.config.key(),
+        admin: ctx.accounts.admin.key(),
+        paused,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+
+use crate::events::AdminTransferred;
+use crate::state::Config;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct TransferAdmin<'info> {
+    #[account( constraint = admin.key() == config.admin)]
+    pub admin: Signer<'info>,
+    pub new_admin: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = ["config".as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+pub fn handle_transfer_admin(ctx: Context<TransferAdmin>) -> Result<()> {
+    ctx.accounts.config.admin = ctx.accounts.new_admin.key();
+
+    emit_cpi!(AdminTransferred {
+        config: ctx.accounts.config.key(),
+        from: ctx.accounts.admin.key(),
+        to: ctx.accounts.new_admin.key(),
+    });
+
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+use crate::events::BuyerTransferred;
+use crate::state::CashSecuredPut;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct TransferBuyerPut<'info> {
+    #[account(constraint = buyer.key() == data.buyer)]
+    pub buyer: Signer<'info>,
+    pub new_buyer: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [
+            "cash-secured-put".as_bytes(),
+            data.creator.as_ref(),
+            &data.nonce.to_le_bytes(),
+        ],
+        bump = data.bump,
+    )]
+    pub data: Account<'info, CashSecuredPut>,
+}
+
+pub fn handle_transfer_buyer_put(ctx: Context<TransferBuyerPut>) -> Result<()> {
+    require!(
+        ctx.accounts.data.amount_premium.is_some(),
+        ErrorCode::OptionNotPurchased
+    );
+
+    require!(
+        !ctx.accounts.data.is_exercised,
+        ErrorCode::OptionAlreadyExercised
+    );
+
+    ctx.accounts.data.buyer = ctx.accounts.new_buyer.key();
+
+    emit_cpi!(BuyerTransferred {
+        option: ctx.accounts.data.key(),
+        from: ctx.accounts.buyer.key(),
+        to: ctx.accounts.new_buyer.key(),
+    });
+
+    Ok(())
+}
@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+
+use crate::events::SellerTransferred;
+use crate::state::CoveredCall;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct TransferSeller<'info> {
+    #[account(constraint = seller.key() == data.seller)]
+    pub seller: Signer<'info>,
+    pub new_seller: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [
+            "covered-call".as_bytes(),
+            data.creator.as_ref(),
+            &data.nonce.to_le_bytes(),
+        ],
+        bump = data.bump,
+    )]
+    pub data: Account<'info, CoveredCall>,
+}
+
+pub fn handle_transfer_seller(ctx: Context<TransferSeller>) -> Result<()> {
+    // Collateral, premium still owed and rent all follow the seller to close
+    ctx.accounts.data.seller = ctx.accounts.new_seller.key();
+
+    emit_cpi!(SellerTransferred {
+        option: ctx.accounts.data.key(),
+        from: ctx.accounts.seller.key(),
+        to: ctx.accounts.new_seller.key(),
+    });
+
+    Ok(())
+}
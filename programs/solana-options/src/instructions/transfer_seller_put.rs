@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+
+use crate::events::SellerTransferred;
+use crate::state::CashSecuredPut;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct TransferSellerPut<'info> {
+    #[account(constraint = seller.key() == data.seller)]
+    pub seller: Signer<'info>,
+    pub new_seller: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [
+            "cash-secured-put".as_bytes(),
+            data.creator.as_ref(),
+            &data.nonce.to_le_bytes(),
+        ],
+        bump = data.bump,
+    )]
+    pub data: Account<'info, CashSecuredPut>,
+}
+
+pub fn handle_transfer_seller_put(ctx: Context<TransferSellerPut>) -> Result<()> {
+    // Collateral, premium still owed and rent all follow the seller to close
+    ctx.accounts.data.seller = ctx.accounts.new_seller.key();
+
+    emit_cpi!(SellerTransferred {
+        option: ctx.accounts.data.key(),
+        from: ctx.accounts.seller.key(),
+        to: ctx.accounts.new_seller.key(),
+    });
+
+    Ok(())
+}
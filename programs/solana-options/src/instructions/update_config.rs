@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+
+use crate::events::ConfigUpdated;
+use crate::state::{Config, ConfigParams};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account( constraint = admin.key() == config.admin)]
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = ["config".as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+// Replaces every parameter, callers should start from the current values
+pub fn handle_update_config(ctx: Context<UpdateConfig>, params: ConfigParams) -> Result<()> {
+    params.validate()?;
+
+    ctx.accounts.config.params = params.clone();
+
+    emit_cpi!(ConfigUpdated {
+        config: ctx.accounts.config.key(),
+        admin: ctx.accounts.admin.key(),
+        params,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+
+use crate::events::MarketUpdated;
+use crate::state::{Config, MarketConfig, MarketParams};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateMarket<'info> {
+    #[account( constraint = admin.key() == config.admin)]
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = ["config".as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [
+            "market".as_bytes(),
+            market.mint_base.as_ref(),
+            market.mint_quote.as_ref(),
+        ],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, MarketConfig>,
+}
+
+// Only affects options created afterwards, the feed can't be changed
+pub fn handle_update_market(ctx: Context<UpdateMarket>, params: MarketParams) -> Result<()> {
+    params.validate()?;
+
+    ctx.accounts.market.params = params.clone();
+
+    emit_cpi!(MarketUpdated {
+        market: ctx.accounts.market.key(),
+        mint_base: ctx.accounts.market.mint_base,
+        mint_quote: ctx.accounts.market.mint_quote,
+        feed_id: ctx.accounts.market.feed_id,
+        params,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,173 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{mint_to, Mint, MintTo, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::error::ErrorCode;
+use crate::events::SeriesWritten;
+use crate::state::{Config, MarketConfig, OptionSeries};
+use crate::token::{get_amount_with_transfer_fee, transfer_checked};
+use crate::PAUSE_WRITE;
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(amount: u64)]
+pub struct Write<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+    #[account(
+        seeds = [
+            "option-series".as_bytes(),
+            series.mint_base.as_ref(),
+            series.mint_quote.as_ref(),
+            &series.feed_id,
+            &series.timestamp_expiry.to_le_bytes(),
+            &series.amount_base.to_le_bytes(),
+            &series.amount_quote.to_le_bytes(),
+            &[series.price_kind as u8],
+        ],
+        bump = series.bump,
+    )]
+    pub series: Account<'info, OptionSeries>,
+    #[account( constraint = mint_base.key() == series.mint_base)]
+    pub mint_base: InterfaceAccount<'info, Mint>,
+    #[account(mut, constraint = mint_option.key() == series.mint_option)]
+    pub mint_option: InterfaceAccount<'info, Mint>,
+    #[account(mut, constraint = mint_writer.key() == series.mint_writer)]
+    pub mint_writer: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        constraint = ata_seller_base.amount >= amount,
+        associated_token::mint = mint_base,
+        associated_token::authority = seller,
+        associated_token::token_program = token_program,
+    )]
+    pub ata_seller_base: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = seller,
+        associated_token::mint = mint_option,
+        associated_token::authority = seller,
+        associated_token::token_program = token_program,
+    )]
+    pub ata_seller_option: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = seller,
+        associated_token::mint = mint_writer,
+        associated_token::authority = seller,
+        associated_token::token_program = token_program,
+    )]
+    pub ata_seller_writer: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint_base,
+        associated_token::authority = series,
+        associated_token::token_program = token_program,
+    )]
+    pub ata_vault_base: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        seeds = ["config".as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        seeds = [
+            "market".as_bytes(),
+            series.mint_base.as_ref(),
+            series.mint_quote.as_ref(),
+        ],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, MarketConfig>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_write<'info>(
+    ctx: Context<'_, '_, '_, 'info, Write<'info>>,
+    amount: u64,
+) -> Result<()> {
+    let clock = Clock::get()?;
+
+    require!(
+        !ctx.accounts.config.is_paused(PAUSE_WRITE),
+        ErrorCode::ProtocolPaused
+    );
+
+    require!(
+        clock.unix_timestamp < ctx.accounts.series.timestamp_expiry,
+        ErrorCode::OptionExpired
+    );
+
+    ctx.accounts.market.check_amount(amount)?;
+
+    // Transfer base to vault, topped up by any transfer fee so every option stays fully backed
+    let amount_deposit =
+        get_amount_with_transfer_fee(&ctx.accounts.mint_base.to_account_info(), amount)?;
+    transfer_checked(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.ata_seller_base.to_account_info(),
+                to: ctx.accounts.ata_vault_base.to_account_info(),
+                mint: ctx.accounts.mint_base.to_account_info(),
+                authority: ctx.accounts.seller.to_account_info(),
+            },
+        )
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+        amount_deposit,
+        ctx.accounts.mint_base.decimals,
+    )?;
+
+    let seeds = [
+        "option-series".as_bytes(),
+        ctx.accounts.series.mint_base.as_ref(),
+        ctx.accounts.series.mint_quote.as_ref(),
+        &ctx.accounts.series.feed_id,
+        &ctx.accounts.series.timestamp_expiry.to_le_bytes(),
+        &ctx.accounts.series.amount_base.to_le_bytes(),
+        &ctx.accounts.series.amount_quote.to_le_bytes(),
+        &[ctx.accounts.series.price_kind as u8],
+        &[ctx.accounts.series.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    // Mint the long side, tradable on its own
+    mint_to(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            MintTo {
+                mint: ctx.accounts.mint_option.to_account_info(),
+                to: ctx.accounts.ata_seller_option.to_account_info(),
+                authority: ctx.accounts.series.to_account_info(),
+            },
+            signer,
+        ),
+        amount,
+    )?;
+
+    // Mint the claim on collateral left after exercise
+    mint_to(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            MintTo {
+                mint: ctx.accounts.mint_writer.to_account_info(),
+                to: ctx.accounts.ata_seller_writer.to_account_info(),
+                authority: ctx.accounts.series.to_account_info(),
+            },
+            signer,
+        ),
+        amount,
+    )?;
+
+    emit_cpi!(SeriesWritten {
+        series: ctx.accounts.series.key(),
+        seller: ctx.accounts.seller.key(),
+        amount,
+    });
+
+    Ok(())
+}
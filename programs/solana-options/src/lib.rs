@@ -1,12 +1,15 @@
 pub mod constants;
 pub mod error;
+pub mod events;
 pub mod instructions;
 pub mod math;
 pub mod state;
+pub mod token;
 
 use anchor_lang::prelude::*;
 
 pub use constants::*;
+pub use events::*;
 pub use instructions::*;
 pub use state::*;
 
@@ -16,32 +19,208 @@ declare_id!("So1ar1uyyJ2bhm4DTN3M2wWkug4trVknn2kdZ2vD2Vh");
 pub mod solana_options {
     use super::*;
 
-    pub fn buy(ctx: Context<Buy>, amount_premium: u64) -> Result<()> {
+    pub fn auto_exercise<'info>(
+        ctx: Context<'_, '_, '_, 'info, AutoExercise<'info>>,
+    ) -> Result<()> {
+        handle_auto_exercise(ctx)
+    }
+
+    pub fn auto_exercise_put<'info>(
+        ctx: Context<'_, '_, '_, 'info, AutoExercisePut<'info>>,
+    ) -> Result<()> {
+        handle_auto_exercise_put(ctx)
+    }
+
+    pub fn buy<'info>(
+        ctx: Context<'_, '_, '_, 'info, Buy<'info>>,
+        amount_premium: u64,
+    ) -> Result<()> {
         handle_buy(ctx, amount_premium)
     }
 
-    pub fn close(ctx: Context<Close>) -> Result<()> {
+    pub fn buy_put<'info>(
+        ctx: Context<'_, '_, '_, 'info, BuyPut<'info>>,
+        amount_premium: u64,
+    ) -> Result<()> {
+        handle_buy_put(ctx, amount_premium)
+    }
+
+    pub fn close<'info>(ctx: Context<'_, '_, '_, 'info, Close<'info>>) -> Result<()> {
         handle_close(ctx)
     }
 
-    pub fn exercise(ctx: Context<Exercise>) -> Result<()> {
+    pub fn close_put<'info>(ctx: Context<'_, '_, '_, 'info, ClosePut<'info>>) -> Result<()> {
+        handle_close_put(ctx)
+    }
+
+    pub fn exercise<'info>(ctx: Context<'_, '_, '_, 'info, Exercise<'info>>) -> Result<()> {
         handle_exercise(ctx)
     }
 
-    pub fn initialize(
-        ctx: Context<Initialize>,
+    pub fn exercise_early<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExerciseEarly<'info>>,
+    ) -> Result<()> {
+        handle_exercise_early(ctx)
+    }
+
+    pub fn exercise_physical<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExercisePhysical<'info>>,
+    ) -> Result<()> {
+        handle_exercise_physical(ctx)
+    }
+
+    pub fn exercise_put<'info>(ctx: Context<'_, '_, '_, 'info, ExercisePut<'info>>) -> Result<()> {
+        handle_exercise_put(ctx)
+    }
+
+    pub fn exercise_scheduled<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExerciseScheduled<'info>>,
+        timestamp: i64,
+    ) -> Result<()> {
+        handle_exercise_scheduled(ctx, timestamp)
+    }
+
+    pub fn exercise_series<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExerciseSeries<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        handle_exercise_series(ctx, amount)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn initialize<'info>(
+        ctx: Context<'_, '_, '_, 'info, Initialize<'info>>,
         amount_base: u64,
         amount_quote: u64,
         timestamp_expiry: i64,
+        amount_premium_ask: u64,
+        nonce: u64,
+        settlement: SettlementMode,
+        style: ExerciseStyle,
+        exercise_dates: Vec<i64>,
+        price_kind: PriceKind,
+    ) -> Result<()> {
+        handle_initialize(
+            ctx,
+            amount_base,
+            amount_quote,
+            timestamp_expiry,
+            amount_premium_ask,
+            nonce,
+            settlement,
+            style,
+            exercise_dates,
+            price_kind,
+        )
+    }
+
+    pub fn initialize_config(ctx: Context<InitializeConfig>, params: ConfigParams) -> Result<()> {
+        handle_initialize_config(ctx, params)
+    }
+
+    pub fn initialize_market(
+        ctx: Context<InitializeMarket>,
+        feed_id: [u8; 32],
+        params: MarketParams,
     ) -> Result<()> {
-        handle_initialize(ctx, amount_base, amount_quote, timestamp_expiry)
+        handle_initialize_market(ctx, feed_id, params)
     }
 
-    pub fn mark_close(ctx: Context<MarkClose>, timestamp_expiry: i64) -> Result<()> {
-        handle_mark_close(ctx, timestamp_expiry)
+    pub fn initialize_put<'info>(
+        ctx: Context<'_, '_, '_, 'info, InitializePut<'info>>,
+        amount_base: u64,
+        amount_quote: u64,
+        timestamp_expiry: i64,
+        amount_premium_ask: u64,
+        nonce: u64,
+    ) -> Result<()> {
+        handle_initialize_put(
+            ctx,
+            amount_base,
+            amount_quote,
+            timestamp_expiry,
+            amount_premium_ask,
+            nonce,
+        )
+    }
+
+    pub fn initialize_series(
+        ctx: Context<InitializeSeries>,
+        amount_base: u64,
+        amount_quote: u64,
+        timestamp_expiry: i64,
+        price_kind: PriceKind,
+    ) -> Result<()> {
+        handle_initialize_series(ctx, amount_base, amount_quote, timestamp_expiry, price_kind)
+    }
+
+    pub fn mark_close(
+        ctx: Context<MarkClose>,
+        timestamp_expiry: i64,
+        feed_id: [u8; 32],
+    ) -> Result<()> {
+        handle_mark_close(ctx, timestamp_expiry, feed_id)
+    }
+
+    pub fn mark_finalize(
+        ctx: Context<MarkFinalize>,
+        timestamp_expiry: i64,
+        feed_id: [u8; 32],
+    ) -> Result<()> {
+        handle_mark_finalize(ctx, timestamp_expiry, feed_id)
     }
 
     pub fn mark(ctx: Context<Mark>, timestamp_expiry: i64) -> Result<()> {
         handle_mark(ctx, timestamp_expiry)
     }
+
+    pub fn redeem<'info>(
+        ctx: Context<'_, '_, '_, 'info, Redeem<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        handle_redeem(ctx, amount)
+    }
+
+    pub fn set_paused(ctx: Context<SetPaused>, paused: u32) -> Result<()> {
+        handle_set_paused(ctx, paused)
+    }
+
+    pub fn transfer_admin(ctx: Context<TransferAdmin>) -> Result<()> {
+        handle_transfer_admin(ctx)
+    }
+
+    pub fn transfer_buyer(ctx: Context<TransferBuyer>) -> Result<()> {
+        handle_transfer_buyer(ctx)
+    }
+
+    pub fn transfer_buyer_put(ctx: Context<TransferBuyerPut>) -> Result<()> {
+        handle_transfer_buyer_put(ctx)
+    }
+
+    pub fn transfer_seller(ctx: Context<TransferSeller>) -> Result<()> {
+        handle_transfer_seller(ctx)
+    }
+
+    pub fn transfer_seller_put(ctx: Context<TransferSellerPut>) -> Result<()> {
+        handle_transfer_seller_put(ctx)
+    }
+
+    pub fn update_config(ctx: Context<UpdateConfig>, params: ConfigParams) -> Result<()> {
+        handle_update_config(ctx, params)
+    }
+
+    pub fn update_market(ctx: Context<UpdateMarket>, params: MarketParams) -> Result<()> {
+        handle_update_market(ctx, params)
+    }
+
+    pub fn withdraw_fees<'info>(
+        ctx: Context<'_, '_, '_, 'info, WithdrawFees<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        handle_withdraw_fees(ctx, amount)
+    }
+
+    pub fn write<'info>(ctx: Context<'_, '_, '_, 'info, Write<'info>>, amount: u64) -> Result<()> {
+        handle_write(ctx, amount)
+    }
 }
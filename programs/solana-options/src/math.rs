@@ -1,49 +1,267 @@
-// Has 8 decimal precision to match pyth price oracle
-pub fn calc_strike(base: u64, quote: u64) -> i64 {
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+
+// Strike expressed in the oracle's price units, i.e. scaled by 10^-exponent like a pyth price
+pub fn calc_strike(
+    amount_base: u64,
+    amount_quote: u64,
+    decimals_base: u8,
+    decimals_quote: u8,
+    exponent: i32,
+) -> Result<i64> {
     // Convert to u128 to avoid overflow and maintain precision
-    let base = u128::from(base);
-    let quote = u128::from(quote);
-    let result = (quote * 10u128.pow(8 + 3) / base) as u64;
-    result.try_into().unwrap()
+    let base = u128::from(amount_base);
+    let quote = u128::from(amount_quote);
+    // Price of one whole base in whole quote is quote * 10^(decimals_base - decimals_quote) / base
+    let power = i32::from(decimals_base) - i32::from(decimals_quote) - exponent;
+    let scale = 10u128
+        .checked_pow(power.unsigned_abs())
+        .ok_or(ErrorCode::MathOverflow)?;
+    let result = if power >= 0 {
+        quote.checked_mul(scale).and_then(|x| x.checked_div(base))
+    } else {
+        base.checked_mul(scale).and_then(|x| quote.checked_div(x))
+    }
+    .ok_or(ErrorCode::MathOverflow)?;
+
+    Ok(i64::try_from(result).map_err(|_| ErrorCode::MathOverflow)?)
 }
 
-pub fn get_settlements(strike: i64, mark: i64, amount: u64) -> [u64; 2] {
+pub fn get_settlements(strike: i64, mark: i64, amount: u64) -> Result<[u64; 2]> {
+    require!(mark > 0, ErrorCode::InvalidOraclePrice);
     if mark <= strike {
-        return [amount, 0];
+        return Ok([amount, 0]);
+    }
+    // Convert to u128 to avoid overflow and maintain precision
+    // Round down the seller
+    let seller =
+        u128::from(amount) * u128::from(strike.unsigned_abs()) / u128::from(mark.unsigned_abs());
+    let seller = u64::try_from(seller).map_err(|_| ErrorCode::MathOverflow)?;
+    let buyer = amount.checked_sub(seller).ok_or(ErrorCode::MathOverflow)?;
+
+    Ok([seller, buyer])
+}
+
+pub fn get_put_settlements(strike: i64, mark: i64, amount: u64) -> Result<[u64; 2]> {
+    require!(mark > 0, ErrorCode::InvalidOraclePrice);
+    if mark >= strike {
+        return Ok([amount, 0]);
     }
     // Convert to u128 to avoid overflow and maintain precision
     // Round down the seller
-    let seller = (u128::from(amount) * u128::from(strike.unsigned_abs())
-        / u128::from(mark.unsigned_abs())) as u64;
+    let seller =
+        u128::from(amount) * u128::from(mark.unsigned_abs()) / u128::from(strike.unsigned_abs());
+    let seller = u64::try_from(seller).map_err(|_| ErrorCode::MathOverflow)?;
+    let buyer = amount.checked_sub(seller).ok_or(ErrorCode::MathOverflow)?;
 
-    [seller, amount - seller]
+    Ok([seller, buyer])
+}
+
+// Buyer share rounded down, so exercising a series in pieces can't pay out more than
+// get_settlements leaves after the seller's share
+pub fn get_buyer_settlement(strike: i64, mark: i64, amount: u64) -> Result<u64> {
+    require!(mark > 0, ErrorCode::InvalidOraclePrice);
+    if mark <= strike {
+        return Ok(0);
+    }
+    let buyer =
+        u128::from(amount) * u128::from(mark.abs_diff(strike)) / u128::from(mark.unsigned_abs());
+
+    Ok(u64::try_from(buyer).map_err(|_| ErrorCode::MathOverflow)?)
+}
+
+// Share of amount in basis points, rounded down so it never exceeds amount
+pub fn calc_bps(amount: u64, bps: u16) -> Result<u64> {
+    require!(bps <= 10_000, ErrorCode::MathOverflow);
+    let share = u128::from(amount) * u128::from(bps) / 10_000;
+
+    Ok(u64::try_from(share).map_err(|_| ErrorCode::MathOverflow)?)
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::math::{calc_strike, get_settlements};
+    use crate::error::ErrorCode;
+    use crate::math::{
+        calc_bps, calc_strike, get_buyer_settlement, get_put_settlements, get_settlements,
+    };
 
     #[test]
     fn test_calc_strike() {
-        assert_eq!(calc_strike(1000, 3500), 3500_0000_0000);
-        assert_eq!(calc_strike(1_000_000_000, 130_000_000), 130_0000_0000);
-        assert_eq!(calc_strike(1_000_000_000, 130_500_000), 130_5000_0000);
-        assert_eq!(calc_strike(10000000, 1370000000000), 137000000_0000_0000); // Created this strike with incorrect decimals
+        assert_eq!(calc_strike(1000, 3500, 9, 6, -8).unwrap(), 3500_0000_0000);
+        assert_eq!(
+            calc_strike(1_000_000_000, 130_000_000, 9, 6, -8).unwrap(),
+            130_0000_0000
+        );
+        assert_eq!(
+            calc_strike(1_000_000_000, 130_500_000, 9, 6, -8).unwrap(),
+            130_5000_0000
+        );
+        // Created this strike with incorrect decimals
+        assert_eq!(
+            calc_strike(10_000_000, 1_370_000_000_000, 9, 6, -8).unwrap(),
+            1_3700_0000_0000_0000
+        );
+    }
+
+    #[test]
+    fn test_calc_strike_decimals() {
+        // (amount_base, amount_quote, decimals_base, decimals_quote, exponent, strike)
+        let cases: [(u64, u64, u8, u8, i32, i64); 8] = [
+            // 1 SOL for 150 USDC
+            (1_000_000_000, 150_000_000, 9, 6, -8, 150_0000_0000),
+            // 1M BONK for 20 USDC, pyth quotes BONK with 10 decimals
+            (100_000_000_000, 20_000_000, 5, 6, -10, 200_000),
+            // 1 JUP for 1.20 USDC with a 6 decimal base
+            (1_000_000, 1_200_000, 6, 6, -8, 1_2000_0000),
+            // 1 WBTC for 60,000 USDC
+            (100_000_000, 60_000_000_000, 8, 6, -8, 6_0000_0000_0000),
+            // 0.5 ETH for 1,500 USDC with an 8 decimal base
+            (50_000_000, 1_500_000_000, 8, 6, -8, 3000_0000_0000),
+            // Quote with more decimals than base makes the scale negative
+            (1, 1_500_000_000, 0, 9, -5, 1_50000),
+            // Same pair quoted with a 5 decimal oracle
+            (1_000_000_000, 150_000_000, 9, 6, -5, 150_00000),
+            // Rounds down when the quote cannot be represented at the exponent
+            (3, 1_000_000_000, 0, 9, -2, 33),
+        ];
+
+        for (base, quote, decimals_base, decimals_quote, exponent, strike) in cases {
+            assert_eq!(
+                calc_strike(base, quote, decimals_base, decimals_quote, exponent).unwrap(),
+                strike,
+                "base {base} quote {quote} decimals {decimals_base}/{decimals_quote} exponent {exponent}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_calc_strike_overflow() {
+        // Strike does not fit in a pyth price
+        assert_eq!(
+            calc_strike(1, u64::MAX, 9, 6, -8).unwrap_err(),
+            ErrorCode::MathOverflow.into()
+        );
+        // Scale does not fit in u128
+        assert_eq!(
+            calc_strike(1, 1, 255, 0, -8).unwrap_err(),
+            ErrorCode::MathOverflow.into()
+        );
+        // Empty base
+        assert_eq!(
+            calc_strike(0, 3500, 9, 6, -8).unwrap_err(),
+            ErrorCode::MathOverflow.into()
+        );
     }
 
     #[test]
     fn test_get_settlements() {
         // Can handle if it is out of the money
-        assert_eq!(get_settlements(130, 120, 1_000), [1_000, 0]);
+        assert_eq!(get_settlements(130, 120, 1_000).unwrap(), [1_000, 0]);
         // Can handle if it is at the money
-        assert_eq!(get_settlements(130, 130, 1_000), [1_000, 0]);
+        assert_eq!(get_settlements(130, 130, 1_000).unwrap(), [1_000, 0]);
 
         // In the money
-        assert_eq!(get_settlements(130, 140, 1_000), [928, 72]); // 928.57, 71.42
+        assert_eq!(get_settlements(130, 140, 1_000).unwrap(), [928, 72]); // 928.57, 71.42
 
         assert_eq!(
-            get_settlements(calc_strike(3000000000, 450000000), 15180059921, 3000000000),
+            get_settlements(
+                calc_strike(3_000_000_000, 450_000_000, 9, 6, -8).unwrap(),
+                15180059921,
+                3_000_000_000
+            )
+            .unwrap(),
             [2964415175, 35584825]
         );
+
+        // 1M BONK struck at 20 USDC marked at 0.000025
+        assert_eq!(
+            get_settlements(
+                calc_strike(100_000_000_000, 20_000_000, 5, 6, -10).unwrap(),
+                250_000,
+                100_000_000_000
+            )
+            .unwrap(),
+            [80_000_000_000, 20_000_000_000]
+        );
+
+        // Rejects non positive marks
+        assert_eq!(
+            get_settlements(130, 0, 1_000).unwrap_err(),
+            ErrorCode::InvalidOraclePrice.into()
+        );
+        assert_eq!(
+            get_settlements(130, -140, 1_000).unwrap_err(),
+            ErrorCode::InvalidOraclePrice.into()
+        );
+    }
+
+    #[test]
+    fn test_get_buyer_settlement() {
+        assert_eq!(get_buyer_settlement(130, 120, 1_000).unwrap(), 0);
+        assert_eq!(get_buyer_settlement(130, 130, 1_000).unwrap(), 0);
+        assert_eq!(get_buyer_settlement(130, 140, 1_000).unwrap(), 71); // 71.42
+
+        // Exercising one unit at a time never beats exercising at once
+        let pieces: u64 = (0..1_000)
+            .map(|_| get_buyer_settlement(130, 140, 1).unwrap())
+            .sum();
+        assert_eq!(pieces, 0);
+
+        assert_eq!(
+            get_buyer_settlement(130, 0, 1_000).unwrap_err(),
+            ErrorCode::InvalidOraclePrice.into()
+        );
+    }
+
+    #[test]
+    fn test_get_put_settlements() {
+        // Can handle if it is out of the money
+        assert_eq!(get_put_settlements(130, 140, 1_000).unwrap(), [1_000, 0]);
+        // Can handle if it is at the money
+        assert_eq!(get_put_settlements(130, 130, 1_000).unwrap(), [1_000, 0]);
+
+        // In the money
+        assert_eq!(get_put_settlements(140, 130, 1_000).unwrap(), [928, 72]); // 928.57, 71.42
+
+        assert_eq!(
+            get_put_settlements(
+                calc_strike(1000, 3500, 9, 6, -8).unwrap(),
+                3000_0000_0000,
+                3500
+            )
+            .unwrap(),
+            [3000, 500]
+        );
+
+        // 1 WBTC put struck at 60,000 USDC marked at 54,000
+        assert_eq!(
+            get_put_settlements(
+                calc_strike(100_000_000, 60_000_000_000, 8, 6, -8).unwrap(),
+                5_4000_0000_0000,
+                60_000_000_000
+            )
+            .unwrap(),
+            [54_000_000_000, 6_000_000_000]
+        );
+
+        // Rejects non positive marks
+        assert_eq!(
+            get_put_settlements(130, 0, 1_000).unwrap_err(),
+            ErrorCode::InvalidOraclePrice.into()
+        );
+    }
+
+    #[test]
+    fn test_calc_bps() {
+        assert_eq!(calc_bps(125, 100).unwrap(), 1); // 1.25
+        assert_eq!(calc_bps(99, 100).unwrap(), 0);
+        assert_eq!(calc_bps(u64::MAX, 10_000).unwrap(), u64::MAX);
+        assert_eq!(calc_bps(1_000, 0).unwrap(), 0);
+
+        assert_eq!(
+            calc_bps(1_000, 10_001).unwrap_err(),
+            ErrorCode::MathOverflow.into()
+        );
     }
 }
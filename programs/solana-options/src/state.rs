@@ -1,5 +1,10 @@
 use anchor_lang::prelude::*;
 
+use crate::error::ErrorCode;
+use crate::{
+    MARK_GRACE_PERIOD, MAX_ALLOWED_MINTS, MAX_EXERCISE_DATES, MAX_MARK_SAMPLES, MIN_SAMPLE_SPACING,
+};
+
 #[account]
 #[derive(InitSpace)]
 pub struct CoveredCall {
@@ -14,6 +19,46 @@ pub struct CoveredCall {
     pub amount_premium: Option<u64>,
     pub is_exercised: bool,
     pub timestamp_created: i64,
+    pub amount_premium_ask: u64,
+    pub mint_premium: Pubkey,
+    pub nonce: u64,
+    pub settlement: SettlementMode,
+    pub feed_id: [u8; 32],
+    pub decimals_base: u8,
+    pub decimals_quote: u8,
+    // Seller at initialize, kept in the seeds so the short side can be transferred
+    pub creator: Pubkey,
+    pub style: ExerciseStyle,
+    // Bermudan dates before expiry, each settled on its own expiry mark
+    #[max_len(MAX_EXERCISE_DATES)]
+    pub exercise_dates: Vec<i64>,
+    pub price_kind: PriceKind,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum SettlementMode {
+    // Buyer receives the in-the-money portion of base
+    Cash,
+    // Buyer pays amount_quote and receives all of amount_base
+    Physical,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum ExerciseStyle {
+    // Exercisable only after expiry against the expiry mark
+    European,
+    // Also exercisable before expiry against a live oracle price
+    American,
+    // Also exercisable on each scheduled date against that date's mark
+    Bermudan,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum PriceKind {
+    // Latest price marked in the window before expiry
+    Spot,
+    // Average of the samples marked across the window, fixed by mark_finalize
+    Average,
 }
 
 #[account]
@@ -25,4 +70,189 @@ pub struct ExpiryData {
     pub publish_time: i64,
     pub bump: u8,
     pub payer: Pubkey,
+    pub feed_id: [u8; 32],
+    // Samples averaged by mark_finalize, at most one per MIN_SAMPLE_SPACING slot before expiry
+    pub price_sum: i128,
+    pub sample_count: u32,
+    // Bit i is set once the slot i * MIN_SAMPLE_SPACING seconds before expiry has a sample
+    pub sample_buckets: u128,
+    pub average_price: i64,
+    pub is_finalized: bool,
+}
+
+impl ExpiryData {
+    // Price an option of this kind settles on, None until it is available
+    pub fn settlement_price(&self, kind: PriceKind) -> Option<i64> {
+        match kind {
+            PriceKind::Spot => (self.price != 0).then_some(self.price),
+            PriceKind::Average => self.is_finalized.then_some(self.average_price),
+        }
+    }
+
+    // Like settlement_price, but only once the price can no longer change
+    pub fn final_settlement_price(
+        &self,
+        kind: PriceKind,
+        timestamp_expiry: i64,
+        now: i64,
+    ) -> Option<i64> {
+        let is_final = match kind {
+            PriceKind::Spot => now >= timestamp_expiry.saturating_add(MARK_GRACE_PERIOD),
+            PriceKind::Average => self.is_finalized,
+        };
+        self.settlement_price(kind).filter(|_| is_final)
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct CashSecuredPut {
+    pub seller: Pubkey,
+    pub buyer: Pubkey,
+    pub amount_base: u64,
+    pub amount_quote: u64,
+    pub timestamp_expiry: i64,
+    pub mint_quote: Pubkey,
+    pub mint_base: Pubkey,
+    pub bump: u8,
+    pub amount_premium: Option<u64>,
+    pub is_exercised: bool,
+    pub timestamp_created: i64,
+    pub amount_premium_ask: u64,
+    pub mint_premium: Pubkey,
+    pub nonce: u64,
+    pub feed_id: [u8; 32],
+    pub decimals_base: u8,
+    pub decimals_quote: u8,
+    // Seller at initialize, kept in the seeds so the short side can be transferred
+    pub creator: Pubkey,
+}
+
+// Fungible covered calls sharing terms, option and writer tokens are minted 1:1 with base deposited
+#[account]
+#[derive(InitSpace)]
+pub struct OptionSeries {
+    pub mint_base: Pubkey,
+    pub mint_quote: Pubkey,
+    pub amount_base: u64,
+    pub amount_quote: u64,
+    pub timestamp_expiry: i64,
+    pub feed_id: [u8; 32],
+    pub decimals_base: u8,
+    pub decimals_quote: u8,
+    pub mint_option: Pubkey,
+    pub mint_writer: Pubkey,
+    pub bump: u8,
+    // Part of the seeds, series on the same terms but priced differently are distinct
+    pub price_kind: PriceKind,
+}
+
+// Program wide settings, a single PDA owned by an admin
+#[account]
+#[derive(InitSpace)]
+pub struct Config {
+    pub admin: Pubkey,
+    pub bump: u8,
+    // Bitmask of PAUSE_* flags, only changed through set_paused
+    pub paused: u32,
+    pub params: ConfigParams,
+}
+
+impl Config {
+    pub fn is_paused(&self, flag: u32) -> bool {
+        self.paused & flag != 0
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub struct ConfigParams {
+    // Seconds before expiry in which a price can be marked
+    pub mark_window: i64,
+    // Widest oracle confidence interval accepted for a settlement price, in basis points of price
+    pub max_conf_bps: u16,
+    // Fee on premiums paid in buy, in basis points
+    pub fee_premium_bps: u16,
+    // Fee on the buyer's in the money amount at exercise, in basis points
+    pub fee_settlement_bps: u16,
+    // Share of the buyer's payout paid to whoever auto exercises, in basis points
+    pub bounty_bps: u16,
+    // Mints exempt from the check on extensions like permanent delegate, transfer hook or
+    // non-transferable
+    #[max_len(MAX_ALLOWED_MINTS)]
+    pub allowed_mints: Vec<Pubkey>,
+}
+
+impl ConfigParams {
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.mark_window > 0
+                // Every sample slot of the window needs a bit in ExpiryData::sample_buckets
+                && self.mark_window <= MIN_SAMPLE_SPACING * MAX_MARK_SAMPLES
+                && 0 < self.max_conf_bps
+                && self.max_conf_bps <= 10_000
+                && self.allowed_mints.len() <= MAX_ALLOWED_MINTS
+                && self.fee_premium_bps <= 10_000
+                // Both come out of the buyer's settlement
+                && self.fee_settlement_bps as u32 + self.bounty_bps as u32 <= 10_000,
+            ErrorCode::InvalidConfig
+        );
+        Ok(())
+    }
+}
+
+// Listing of a (base, quote) pair, options can only be created on listed pairs
+#[account]
+#[derive(InitSpace)]
+pub struct MarketConfig {
+    pub bump: u8,
+    pub mint_base: Pubkey,
+    pub mint_quote: Pubkey,
+    // Fixed at listing since marks and open options depend on it
+    pub feed_id: [u8; 32],
+    pub params: MarketParams,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub struct MarketParams {
+    // Bounds on seconds from creation to expiry
+    pub min_time_to_expiry: i64,
+    pub max_time_to_expiry: i64,
+    // Smallest amount_base an option can be created with
+    pub min_amount_base: u64,
+}
+
+impl MarketConfig {
+    pub fn check_option(&self, amount_base: u64, timestamp_expiry: i64, now: i64) -> Result<()> {
+        self.check_expiry(timestamp_expiry, now)?;
+        self.check_amount(amount_base)
+    }
+
+    pub fn check_expiry(&self, timestamp_expiry: i64, now: i64) -> Result<()> {
+        require!(
+            timestamp_expiry - now >= self.params.min_time_to_expiry
+                && timestamp_expiry - now <= self.params.max_time_to_expiry,
+            ErrorCode::ExpiryOutOfRange
+        );
+        Ok(())
+    }
+
+    pub fn check_amount(&self, amount_base: u64) -> Result<()> {
+        require!(
+            amount_base >= self.params.min_amount_base,
+            ErrorCode::AmountTooSmall
+        );
+        Ok(())
+    }
+}
+
+impl MarketParams {
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            0 <= self.min_time_to_expiry
+                && self.min_time_to_expiry <= self.max_time_to_expiry
+                && self.min_amount_base > 0,
+            ErrorCode::InvalidMarket
+        );
+        Ok(())
+    }
 }
@@ -0,0 +1,137 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    harvest_withheld_tokens_to_mint,
+    spl_token_2022::{
+        extension::{
+            transfer_fee::TransferFeeConfig, BaseStateWithExtensions, ExtensionType,
+            StateWithExtensions,
+        },
+        onchain::invoke_transfer_checked,
+        state::Mint,
+    },
+    HarvestWithheldTokensToMint, TransferChecked,
+};
+
+use crate::error::ErrorCode;
+use crate::state::Config;
+
+// Extensions that let a third party move, block or freeze tokens held by the program, keep them
+// from leaving a vault, or close the mint and re-create it
+const RESTRICTED_EXTENSIONS: [ExtensionType; 5] = [
+    ExtensionType::PermanentDelegate,
+    ExtensionType::TransferHook,
+    ExtensionType::NonTransferable,
+    ExtensionType::DefaultAccountState,
+    ExtensionType::MintCloseAuthority,
+];
+
+// Mints with a restricted extension can only be used once the admin allows them
+pub fn check_mint(mint: &AccountInfo, config: &Config) -> Result<()> {
+    if config.params.allowed_mints.contains(mint.key) {
+        return Ok(());
+    }
+
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<Mint>::unpack(&data)?;
+    // Extensions newer than this program, like pausable, fail to parse and can't be trusted either
+    let extensions = state
+        .get_extension_types()
+        .map_err(|_| ErrorCode::MintExtensionNotAllowed)?;
+    require!(
+        !extensions.iter().any(|x| RESTRICTED_EXTENSIONS.contains(x)),
+        ErrorCode::MintExtensionNotAllowed
+    );
+    Ok(())
+}
+
+// Amount to send so that `amount` arrives after the mint's transfer fee, if it has one
+pub fn get_amount_with_transfer_fee(mint: &AccountInfo, amount: u64) -> Result<u64> {
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<Mint>::unpack(&data)?;
+    let Ok(transfer_fee) = state.get_extension::<TransferFeeConfig>() else {
+        return Ok(amount);
+    };
+
+    transfer_fee
+        .calculate_inverse_epoch_fee(Clock::get()?.epoch, amount)
+        .and_then(|x| amount.checked_add(x))
+        .ok_or(error!(ErrorCode::MathOverflow))
+}
+
+// Fees withheld on deposits must be moved to the mint before a vault can be closed
+pub fn harvest_transfer_fees<'info>(
+    token_program: AccountInfo<'info>,
+    mint: AccountInfo<'info>,
+    account: AccountInfo<'info>,
+) -> Result<()> {
+    let has_transfer_fee = {
+        let data = mint.try_borrow_data()?;
+        let state = StateWithExtensions::<Mint>::unpack(&data)?;
+        state.get_extension::<TransferFeeConfig>().is_ok()
+    };
+    if !has_transfer_fee {
+        return Ok(());
+    }
+
+    harvest_withheld_tokens_to_mint(
+        CpiContext::new(
+            token_program.clone(),
+            HarvestWithheldTokensToMint {
+                token_program_id: token_program,
+                mint,
+            },
+        ),
+        vec![account],
+    )
+}
+
+// Like token_interface::transfer_checked, but passes the remaining accounts on so
+// allowed transfer hook mints can resolve the extra accounts their hook needs
+pub fn transfer_checked<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, TransferChecked<'info>>,
+    amount: u64,
+    decimals: u8,
+) -> Result<()> {
+    invoke_transfer_checked(
+        ctx.program.key,
+        ctx.accounts.from,
+        ctx.accounts.mint,
+        ctx.accounts.to,
+        ctx.accounts.authority,
+        &ctx.remaining_accounts,
+        amount,
+        decimals,
+        ctx.signer_seeds,
+    )
+    .map_err(Into::into)
+}
+
+// Pays `amount` out of a vault the program signs for, and `fee` from the same vault to the
+// protocol fee vault, which can be left out when there is no fee
+pub fn pay_out_with_fee<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, TransferChecked<'info>>,
+    fee_vault: Option<AccountInfo<'info>>,
+    amount: u64,
+    fee: u64,
+    decimals: u8,
+) -> Result<()> {
+    if fee > 0 {
+        let fee_vault = fee_vault.ok_or(ErrorCode::FeeVaultRequired)?;
+        transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.program.clone(),
+                TransferChecked {
+                    from: ctx.accounts.from.clone(),
+                    mint: ctx.accounts.mint.clone(),
+                    to: fee_vault,
+                    authority: ctx.accounts.authority.clone(),
+                },
+                ctx.signer_seeds,
+            )
+            .with_remaining_accounts(ctx.remaining_accounts.clone()),
+            fee,
+            decimals,
+        )?;
+    }
+    transfer_checked(ctx, amount, decimals)
+}
@@ -12,6 +12,13 @@ expect.extend({
     };
   },
 });
+export const SOL_FEED_ID = Array.from(
+  Buffer.from(
+    "ef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d",
+    "hex",
+  ),
+);
+
 export function getStrikePrice(
   amountBase: bigint,
   amountQuote: bigint,
@@ -24,12 +31,7 @@ export function getQuoteAmountWithStrike(amountBase: bigint, strike: number) {
 }
 
 export function getPda(seeds: {
-  amountBase: bigint;
-  amountQuote: bigint;
-  buyer: PublicKey;
-  expiry: bigint;
-  mintBase: PublicKey;
-  mintQuote: PublicKey;
+  nonce: bigint;
   programId: PublicKey;
   seller: PublicKey;
 }) {
@@ -37,22 +39,38 @@ export function getPda(seeds: {
     [
       Buffer.from("covered-call"),
       seeds.seller.toBuffer(),
-      seeds.buyer.toBuffer(),
-      seeds.mintBase.toBuffer(),
-      seeds.mintQuote.toBuffer(),
-      new BN(seeds.amountBase.toString()).toArrayLike(Buffer, "le", 8),
-      new BN(seeds.amountQuote.toString()).toArrayLike(Buffer, "le", 8),
-      new BN(seeds.expiry.toString()).toArrayLike(Buffer, "le", 8),
+      new BN(seeds.nonce.toString()).toArrayLike(Buffer, "le", 8),
+    ],
+    seeds.programId,
+  );
+  return pda;
+}
+
+export function getPutPda(seeds: {
+  nonce: bigint;
+  programId: PublicKey;
+  seller: PublicKey;
+}) {
+  const [pda] = PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("cash-secured-put"),
+      seeds.seller.toBuffer(),
+      new BN(seeds.nonce.toString()).toArrayLike(Buffer, "le", 8),
     ],
     seeds.programId,
   );
   return pda;
 }
 
-export function getExpiryPda(seeds: { expiry: Date; programId: PublicKey }) {
+export function getExpiryPda(seeds: {
+  expiry: Date;
+  feedId: number[];
+  programId: PublicKey;
+}) {
   const [pda] = PublicKey.findProgramAddressSync(
     [
       Buffer.from("expiry-meta"),
+      Buffer.from(seeds.feedId),
       new BN(Math.floor(seeds.expiry.getTime() / 1000).toString()).toArrayLike(
         Buffer,
         "le",
@@ -63,3 +81,61 @@ export function getExpiryPda(seeds: { expiry: Date; programId: PublicKey }) {
   );
   return pda;
 }
+
+export function getSeriesPda(seeds: {
+  amountBase: bigint;
+  amountQuote: bigint;
+  expiry: bigint;
+  feedId: number[];
+  mintBase: PublicKey;
+  mintQuote: PublicKey;
+  priceKind?: "spot" | "average";
+  programId: PublicKey;
+}) {
+  const [series] = PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("option-series"),
+      seeds.mintBase.toBuffer(),
+      seeds.mintQuote.toBuffer(),
+      Buffer.from(seeds.feedId),
+      new BN(seeds.expiry.toString()).toArrayLike(Buffer, "le", 8),
+      new BN(seeds.amountBase.toString()).toArrayLike(Buffer, "le", 8),
+      new BN(seeds.amountQuote.toString()).toArrayLike(Buffer, "le", 8),
+      Buffer.from([seeds.priceKind === "average" ? 1 : 0]),
+    ],
+    seeds.programId,
+  );
+  const [mintOption] = PublicKey.findProgramAddressSync(
+    [Buffer.from("option-mint"), series.toBuffer()],
+    seeds.programId,
+  );
+  const [mintWriter] = PublicKey.findProgramAddressSync(
+    [Buffer.from("writer-mint"), series.toBuffer()],
+    seeds.programId,
+  );
+  return { series, mintOption, mintWriter };
+}
+
+export function getFeeVaultPda(seeds: { mint: PublicKey; programId: PublicKey }) {
+  const [pda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("fee-vault"), seeds.mint.toBuffer()],
+    seeds.programId,
+  );
+  return pda;
+}
+
+export function getMarketPda(seeds: {
+  mintBase: PublicKey;
+  mintQuote: PublicKey;
+  programId: PublicKey;
+}) {
+  const [pda] = PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("market"),
+      seeds.mintBase.toBuffer(),
+      seeds.mintQuote.toBuffer(),
+    ],
+    seeds.programId,
+  );
+  return pda;
+}
@@ -15,6 +15,7 @@ import {
   createSyncNativeInstruction,
   getAssociatedTokenAddressSync,
   NATIVE_MINT,
+  TOKEN_PROGRAM_ID,
 } from "@solana/spl-token";
 import {
   Connection,
@@ -28,7 +29,12 @@ import {
 
 import { SolanaOptions } from "../target/types/solana_options";
 import IDL from "../target/idl/solana_options.json";
-import { getPda, getQuoteAmountWithStrike } from "./helpers";
+import {
+  getMarketPda,
+  getPda,
+  getQuoteAmountWithStrike,
+  SOL_FEED_ID,
+} from "./helpers";
 import { parseUnits } from "./viem";
 import { PythSolanaReceiver } from "@pythnetwork/pyth-solana-receiver";
 
@@ -110,6 +116,7 @@ describe.skip(
     const amountPremium = parseUnits("0.02", 9);
 
     const expiry = new BN(Math.floor(Date.now() / 1000) + 5);
+    const nonce = new BN(Date.now());
 
     const usdcKeypair = Keypair.fromSecretKey(
       Buffer.from(JSON.parse(fs.readFileSync("./.secrets/usdc.json", "utf-8")))
@@ -130,12 +137,7 @@ describe.skip(
       seller.publicKey
     );
     const pda = getPda({
-      amountQuote: amountQuote,
-      amountBase: amountBase,
-      buyer: buyer.publicKey,
-      expiry: BigInt(expiry.toString()),
-      mintQuote: usdc,
-      mintBase: NATIVE_MINT,
+      nonce: BigInt(nonce.toString()),
       programId: programId,
       seller: provider.wallet.publicKey,
     });
@@ -189,7 +191,13 @@ describe.skip(
         .initialize(
           new BN(amountBase.toString()),
           new BN(amountQuote.toString()),
-          expiry
+          expiry,
+          new BN(amountPremium.toString()),
+          nonce,
+          { cash: {} },
+          { european: {} },
+          [],
+          { spot: {} }
         )
         .preInstructions([
           createAssociatedTokenAccountInstruction(
@@ -209,7 +217,10 @@ describe.skip(
           buyer: buyer.publicKey,
           mintQuote: usdc,
           mintBase: NATIVE_MINT,
+          mintPremium: NATIVE_MINT,
+          nonce: expect.toBeBN(nonce),
           seller: seller.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
         })
         .postInstructions([
           createCloseAccountInstruction(
@@ -225,14 +236,25 @@ describe.skip(
 
       expect(await program.account.coveredCall.fetch(pda)).toStrictEqual({
         amountPremium: null,
+        amountPremiumAsk: expect.toBeBN(new BN(amountPremium.toString())),
         amountQuote: expect.toBeBN(new BN(amountQuote.toString())),
         amountBase: expect.toBeBN(new BN(amountBase.toString())),
         buyer: buyer.publicKey,
         timestampExpiry: expect.toBeBN(expiry),
         mintQuote: usdc,
         mintBase: NATIVE_MINT,
+        mintPremium: NATIVE_MINT,
+        nonce: expect.toBeBN(nonce),
         seller: seller.publicKey,
+        settlement: { cash: {} },
+        style: { european: {} },
+        exerciseDates: [],
+        priceKind: { spot: {} },
         bump: expect.any(Number),
+        creator: seller.publicKey,
+        decimalsBase: 9,
+        decimalsQuote: 6,
+        feedId: SOL_FEED_ID,
         isExercised: false,
         timestampCreated: expect.any(BN),
       });
@@ -257,9 +279,11 @@ describe.skip(
         ])
         .accounts({
           data: pda,
+          seller: seller.publicKey,
           buyer: buyer.publicKey,
           payer: buyer.publicKey,
           mintPremium: NATIVE_MINT,
+          tokenProgram: TOKEN_PROGRAM_ID,
         })
         .postInstructions([
           createCloseAccountInstruction(
@@ -276,14 +300,25 @@ describe.skip(
       // Expect amount Premium to be set
       expect(await program.account.coveredCall.fetch(pda)).toStrictEqual({
         amountPremium: expect.toBeBN(new BN(amountPremium.toString())),
+        amountPremiumAsk: expect.toBeBN(new BN(amountPremium.toString())),
         amountQuote: expect.toBeBN(new BN(amountQuote.toString())),
         amountBase: expect.toBeBN(new BN(amountBase.toString())),
         buyer: buyer.publicKey,
         timestampExpiry: expect.toBeBN(expiry),
         mintQuote: usdc,
         mintBase: NATIVE_MINT,
+        mintPremium: NATIVE_MINT,
+        nonce: expect.toBeBN(nonce),
         seller: seller.publicKey,
+        settlement: { cash: {} },
+        style: { european: {} },
+        exerciseDates: [],
+        priceKind: { spot: {} },
         bump: expect.any(Number),
+        creator: seller.publicKey,
+        decimalsBase: 9,
+        decimalsQuote: 6,
+        feedId: SOL_FEED_ID,
         isExercised: false,
         timestampCreated: expect.any(BN),
       });
@@ -303,6 +338,11 @@ describe.skip(
         .accounts({
           payer: payer.publicKey,
           priceUpdate,
+          market: getMarketPda({
+            mintBase: NATIVE_MINT,
+            mintQuote: usdc,
+            programId,
+          }),
         })
         .signers([payer])
         .rpc();
@@ -330,6 +370,7 @@ describe.skip(
           mintQuote: usdc,
           data: pda,
           buyer: buyer.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
         })
         .postInstructions([
           createCloseAccountInstruction(
@@ -345,14 +386,25 @@ describe.skip(
       // Expect is exercised to be set
       expect(await program.account.coveredCall.fetch(pda)).toStrictEqual({
         amountPremium: expect.toBeBN(new BN(amountPremium.toString())),
+        amountPremiumAsk: expect.toBeBN(new BN(amountPremium.toString())),
         amountQuote: expect.toBeBN(new BN(amountQuote.toString())),
         amountBase: expect.toBeBN(new BN(amountBase.toString())),
         buyer: buyer.publicKey,
         timestampExpiry: expect.toBeBN(expiry),
         mintQuote: usdc,
         mintBase: NATIVE_MINT,
+        mintPremium: NATIVE_MINT,
+        nonce: expect.toBeBN(nonce),
         seller: seller.publicKey,
+        settlement: { cash: {} },
+        style: { european: {} },
+        exerciseDates: [],
+        priceKind: { spot: {} },
         bump: expect.any(Number),
+        creator: seller.publicKey,
+        decimalsBase: 9,
+        decimalsQuote: 6,
+        feedId: SOL_FEED_ID,
         isExercised: true,
         timestampCreated: expect.any(BN),
       });
@@ -374,7 +426,7 @@ describe.skip(
           data: pda,
           seller: seller.publicKey,
           payer: seller.publicKey,
-          buyer: buyer.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
         })
         .postInstructions([
           createCloseAccountInstruction(
@@ -389,15 +441,6 @@ describe.skip(
       log("Closed option", tx);
     });
 
-    it("can close expiry account", async () => {
-      const tx = await program.methods
-        .markClose(expiry)
-        .signers([seller])
-        .rpc();
-
-      log("Closed expiry account", tx);
-    });
-
     it("Can reset balances", async () => {
       const transaction = new VersionedTransaction(
         new TransactionMessage({
@@ -1,6 +1,8 @@
 import { describe, it, expect } from "vitest";
+import fs from "fs";
 import {
   startAnchor,
+  AddedAccount,
   BanksClient,
   ProgramTestContext,
   Clock,
@@ -19,14 +21,45 @@ import {
   mintTo,
   getAccount,
 } from "spl-token-bankrun";
-import { Keypair, LAMPORTS_PER_SOL, PublicKey, Signer } from "@solana/web3.js";
-import { getExpiryPda, getPda, getStrikePrice } from "./helpers.js";
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  Signer,
+  SystemProgram,
+  Transaction,
+  TransactionInstruction,
+} from "@solana/web3.js";
+import {
+  getExpiryPda,
+  getFeeVaultPda,
+  getMarketPda,
+  getPda,
+  getPutPda,
+  getSeriesPda,
+  getStrikePrice,
+  SOL_FEED_ID,
+} from "./helpers.js";
 import { getI32Codec, getI64Codec, getU64Codec } from "@solana/codecs-numbers";
 
 const authority = anchor.web3.Keypair.generate();
+const BPF_LOADER_UPGRADEABLE_ID = new PublicKey(
+  "BPFLoaderUpgradeab1e11111111111111111111111"
+);
 const priceUpdate = new PublicKey(
   "7UVimffxr9ow1uXYxsr4LHAcV58mLzhmwaeKvJ1pjLiE"
 );
+// First bytes of the self-CPI carrying an emit_cpi! event
+const EVENT_IX_TAG_LE = Buffer.from("e445a52e51cb9a1d", "hex");
+// Seconds after expiry until marks are fixed
+const MARK_GRACE_PERIOD = new BN(5 * 60);
+// Seconds after the grace period until expired marks can be closed
+const MARK_RETENTION_PERIOD = new BN(30 * 24 * 60 * 60);
+const marketParams = {
+  minTimeToExpiry: new BN(0),
+  maxTimeToExpiry: new BN(365 * 24 * 60 * 60),
+  minAmountBase: new BN(1),
+};
 async function getAtaTokenBalance(
   client: BanksClient,
   mint: PublicKey,
@@ -84,6 +117,96 @@ async function fundAtaAccount(
   );
 }
 
+async function createMint2022(
+  context: ProgramTestContext,
+  provider: BankrunProvider,
+  extensions: token.ExtensionType[],
+  initExtensions: (mint: PublicKey) => TransactionInstruction[]
+) {
+  const mint = Keypair.generate();
+  const space = token.getMintLen(extensions);
+  const rent = await context.banksClient.getRent();
+
+  await provider.sendAndConfirm(
+    new Transaction().add(
+      SystemProgram.createAccount({
+        fromPubkey: provider.wallet.publicKey,
+        newAccountPubkey: mint.publicKey,
+        space,
+        lamports: Number(rent.minimumBalance(BigInt(space))),
+        programId: token.TOKEN_2022_PROGRAM_ID,
+      }),
+      ...initExtensions(mint.publicKey),
+      token.createInitializeMintInstruction(
+        mint.publicKey,
+        9,
+        authority.publicKey,
+        null,
+        token.TOKEN_2022_PROGRAM_ID
+      )
+    ),
+    [mint]
+  );
+
+  return mint.publicKey;
+}
+
+async function fundAtaAccount2022(
+  provider: BankrunProvider,
+  mint: PublicKey,
+  user: PublicKey,
+  amount: bigint | number
+) {
+  const ata = token.getAssociatedTokenAddressSync(
+    mint,
+    user,
+    true,
+    token.TOKEN_2022_PROGRAM_ID
+  );
+
+  await provider.sendAndConfirm(
+    new Transaction().add(
+      token.createAssociatedTokenAccountInstruction(
+        provider.wallet.publicKey,
+        ata,
+        user,
+        mint,
+        token.TOKEN_2022_PROGRAM_ID
+      ),
+      token.createMintToInstruction(
+        mint,
+        ata,
+        authority.publicKey,
+        amount,
+        [],
+        token.TOKEN_2022_PROGRAM_ID
+      )
+    ),
+    [authority]
+  );
+}
+
+async function getAtaTokenBalance2022(
+  client: BanksClient,
+  mint: PublicKey,
+  user: PublicKey
+) {
+  const ata = token.getAssociatedTokenAddressSync(
+    mint,
+    user,
+    true,
+    token.TOKEN_2022_PROGRAM_ID
+  );
+  const info = await client.getAccount(ata);
+  if (!info) return BigInt(0);
+
+  return token.unpackAccount(
+    ata,
+    { ...info, data: Buffer.from(info.data) },
+    token.TOKEN_2022_PROGRAM_ID
+  ).amount;
+}
+
 const warpTo = async (context: ProgramTestContext, ms: anchor.BN) => {
   const currentClock = await context.banksClient.getClock();
   context.setClock(
@@ -97,11 +220,57 @@ const warpTo = async (context: ProgramTestContext, ms: anchor.BN) => {
   );
 };
 
-const fixtureDeployed = async () => {
-  const context = await startAnchor(".", [], []);
-  const payer = context.payer;
+// Deploys the program behind the upgradeable loader, as initialize_config
+// checks the upgrade authority
+const upgradeableProgram = (upgradeAuthority: PublicKey): AddedAccount[] => {
+  const programId = new PublicKey(IDL.address);
+  const [programData] = PublicKey.findProgramAddressSync(
+    [programId.toBuffer()],
+    BPF_LOADER_UPGRADEABLE_ID
+  );
+  const u32 = (value: number) => {
+    const buff = Buffer.alloc(4);
+    buff.writeUInt32LE(value);
+    return buff;
+  };
+
+  return [
+    {
+      address: programId,
+      info: {
+        data: Buffer.concat([u32(2), programData.toBuffer()]),
+        executable: true,
+        lamports: LAMPORTS_PER_SOL,
+        owner: BPF_LOADER_UPGRADEABLE_ID,
+      },
+    },
+    {
+      address: programData,
+      info: {
+        data: Buffer.concat([
+          u32(3), // ProgramData
+          Buffer.alloc(8), // Slot
+          Buffer.from([1]), // Some
+          upgradeAuthority.toBuffer(),
+          fs.readFileSync("target/deploy/solana_options.so"),
+        ]),
+        executable: false,
+        lamports: 100 * LAMPORTS_PER_SOL,
+        owner: BPF_LOADER_UPGRADEABLE_ID,
+      },
+    },
+  ];
+};
+
+const fixtureStarted = async () => {
   const buyer = Keypair.generate();
   const seller = Keypair.generate();
+  const context = await startAnchor(
+    ".",
+    [],
+    upgradeableProgram(seller.publicKey)
+  );
+  const payer = context.payer;
   await Promise.all([
     airdrop(context, buyer.publicKey, 1 * LAMPORTS_PER_SOL),
     airdrop(context, seller.publicKey, 1 * LAMPORTS_PER_SOL),
@@ -111,6 +280,30 @@ const fixtureDeployed = async () => {
 
   // @ts-ignore
   const program = new Program<SolanaOptions>(IDL, provider);
+  const [programData] = PublicKey.findProgramAddressSync(
+    [program.programId.toBuffer()],
+    BPF_LOADER_UPGRADEABLE_ID
+  );
+
+  return { context, program, provider, buyer, seller, payer, programData };
+};
+
+const fixtureDeployed = async () => {
+  const { context, program, provider, buyer, seller, payer, programData } =
+    await fixtureStarted();
+
+  // Seller is the provider wallet and upgrade authority, so becomes the admin
+  await program.methods
+    .initializeConfig({
+      markWindow: new BN(30 * 60),
+      maxConfBps: 100,
+      feePremiumBps: 0,
+      feeSettlementBps: 0,
+      bountyBps: 100,
+      allowedMints: [],
+    })
+    .accounts({ programData })
+    .rpc();
 
   const [wsol, usdc] = await Promise.all([
     createMint(
@@ -129,6 +322,21 @@ const fixtureDeployed = async () => {
     ),
   ]);
 
+  await program.methods
+    .initializeMarket(SOL_FEED_ID, marketParams)
+    .accounts({
+      mintBase: wsol,
+      mintQuote: usdc,
+      tokenProgram: token.TOKEN_PROGRAM_ID,
+      tokenProgramQuote: token.TOKEN_PROGRAM_ID,
+    })
+    .rpc();
+  const market = getMarketPda({
+    mintBase: wsol,
+    mintQuote: usdc,
+    programId: program.programId,
+  });
+
   await Promise.all([
     fundAtaAccount(context.banksClient, wsol, seller, BigInt(1000)),
     fundAtaAccount(context.banksClient, wsol, buyer, BigInt(1000)),
@@ -168,6 +376,7 @@ const fixtureDeployed = async () => {
     buyer,
     payer,
     setPrice,
+    market,
   };
 };
 
@@ -179,22 +388,65 @@ const fixtureInitialized = async () => {
     .initialize(
       new anchor.BN("1000"),
       new anchor.BN("3500"),
-      new anchor.BN(expiry)
+      new anchor.BN(expiry),
+      new anchor.BN(10),
+      new anchor.BN(0),
+      { cash: {} },
+      { european: {} },
+      [],
+      { spot: {} }
     )
     .accounts({
       mintBase: wsol,
+      mintPremium: wsol,
       mintQuote: usdc,
       buyer: buyer.publicKey,
+      tokenProgram: token.TOKEN_PROGRAM_ID,
+      tokenProgramPremium: token.TOKEN_PROGRAM_ID,
     })
     .rpc();
 
   const pda = getPda({
-    amountQuote: 3500n,
-    amountBase: 1000n,
-    buyer: buyer.publicKey,
-    expiry: BigInt(expiry.toString()),
-    mintQuote: usdc,
-    mintBase: wsol,
+    nonce: 0n,
+    programId: program.programId,
+    seller: seller.publicKey,
+  });
+
+  return {
+    expiry,
+    pda,
+    ...fixture,
+  };
+};
+
+const fixtureOpenOffer = async () => {
+  const fixture = await fixtureDeployed();
+  const { program, wsol, usdc, seller } = fixture;
+  const expiry = new anchor.BN(Math.floor(Date.now() / 1000) + 180);
+  await program.methods
+    .initialize(
+      new anchor.BN("1000"),
+      new anchor.BN("3500"),
+      new anchor.BN(expiry),
+      new anchor.BN(10),
+      new anchor.BN(1),
+      { cash: {} },
+      { european: {} },
+      [],
+      { spot: {} }
+    )
+    .accounts({
+      mintBase: wsol,
+      mintPremium: wsol,
+      mintQuote: usdc,
+      buyer: null,
+      tokenProgram: token.TOKEN_PROGRAM_ID,
+      tokenProgramPremium: token.TOKEN_PROGRAM_ID,
+    })
+    .rpc();
+
+  const pda = getPda({
+    nonce: 1n,
     programId: program.programId,
     seller: seller.publicKey,
   });
@@ -208,15 +460,17 @@ const fixtureInitialized = async () => {
 
 const fixtureBought = async () => {
   const fixture = await fixtureInitialized();
-  const { program, pda, buyer, wsol } = fixture;
+  const { program, pda, seller, buyer, wsol } = fixture;
 
   await program.methods
     .buy(new anchor.BN(10))
     .accounts({
       data: pda,
+      seller: seller.publicKey,
       buyer: buyer.publicKey,
       mintPremium: wsol,
       payer: buyer.publicKey,
+      tokenProgram: token.TOKEN_PROGRAM_ID,
     })
     .signers([buyer])
     .rpc();
@@ -226,12 +480,15 @@ const fixtureBought = async () => {
 
 const fixtureExercised = async () => {
   const fixture = await fixtureBought();
-  const { program, pda, buyer, wsol, context, usdc, setPrice, expiry } =
+  const { program, pda, buyer, wsol, context, usdc, setPrice, expiry, market } =
     fixture;
 
   setPrice(4000);
-  await program.methods.mark(expiry).accounts({ priceUpdate }).rpc();
-  await warpTo(context, expiry.add(new anchor.BN(10)));
+  await program.methods
+    .mark(expiry)
+    .accounts({ priceUpdate, market })
+    .rpc();
+  await warpTo(context, expiry.add(MARK_GRACE_PERIOD));
 
   // Create and fund the ata account for the buyer
   await fundAtaAccount(context.banksClient, usdc, buyer, BigInt(3500));
@@ -242,6 +499,67 @@ const fixtureExercised = async () => {
       mintQuote: usdc,
       data: pda,
       buyer: buyer.publicKey,
+      tokenProgram: token.TOKEN_PROGRAM_ID,
+    })
+    .signers([buyer])
+    .rpc();
+
+  return fixture;
+};
+
+const fixturePutInitialized = async () => {
+  const fixture = await fixtureDeployed();
+  const { context, program, wsol, usdc, buyer, seller } = fixture;
+  await Promise.all([
+    fundAtaAccount(context.banksClient, usdc, seller, BigInt(3500)),
+    fundAtaAccount(context.banksClient, usdc, buyer, BigInt(1000)),
+  ]);
+
+  const expiry = new anchor.BN(Math.floor(Date.now() / 1000) + 180);
+  await program.methods
+    .initializePut(
+      new anchor.BN("1000"),
+      new anchor.BN("3500"),
+      new anchor.BN(expiry),
+      new anchor.BN(10),
+      new anchor.BN(0)
+    )
+    .accounts({
+      mintBase: wsol,
+      mintPremium: usdc,
+      mintQuote: usdc,
+      buyer: buyer.publicKey,
+      tokenProgram: token.TOKEN_PROGRAM_ID,
+      tokenProgramPremium: token.TOKEN_PROGRAM_ID,
+    })
+    .rpc();
+
+  const pda = getPutPda({
+    nonce: 0n,
+    programId: program.programId,
+    seller: seller.publicKey,
+  });
+
+  return {
+    expiry,
+    pda,
+    ...fixture,
+  };
+};
+
+const fixturePutBought = async () => {
+  const fixture = await fixturePutInitialized();
+  const { program, pda, seller, buyer, usdc } = fixture;
+
+  await program.methods
+    .buyPut(new anchor.BN(10))
+    .accounts({
+      data: pda,
+      seller: seller.publicKey,
+      buyer: buyer.publicKey,
+      mintPremium: usdc,
+      payer: buyer.publicKey,
+      tokenProgram: token.TOKEN_PROGRAM_ID,
     })
     .signers([buyer])
     .rpc();
@@ -249,6 +567,60 @@ const fixtureExercised = async () => {
   return fixture;
 };
 
+const fixturePhysicalBought = async () => {
+  const fixture = await fixtureDeployed();
+  const { context, program, wsol, usdc, buyer, seller } = fixture;
+  const expiry = new anchor.BN(Math.floor(Date.now() / 1000) + 180);
+  await program.methods
+    .initialize(
+      new anchor.BN("1000"),
+      new anchor.BN("3500"),
+      new anchor.BN(expiry),
+      new anchor.BN(10),
+      new anchor.BN(2),
+      { physical: {} },
+      { european: {} },
+      [],
+      { spot: {} }
+    )
+    .accounts({
+      mintBase: wsol,
+      mintPremium: wsol,
+      mintQuote: usdc,
+      buyer: buyer.publicKey,
+      tokenProgram: token.TOKEN_PROGRAM_ID,
+      tokenProgramPremium: token.TOKEN_PROGRAM_ID,
+    })
+    .rpc();
+
+  const pda = getPda({
+    nonce: 2n,
+    programId: program.programId,
+    seller: seller.publicKey,
+  });
+
+  await program.methods
+    .buy(new anchor.BN(10))
+    .accounts({
+      data: pda,
+      seller: seller.publicKey,
+      buyer: buyer.publicKey,
+      mintPremium: wsol,
+      payer: buyer.publicKey,
+      tokenProgram: token.TOKEN_PROGRAM_ID,
+    })
+    .signers([buyer])
+    .rpc();
+
+  await fundAtaAccount(context.banksClient, usdc, buyer, BigInt(3500));
+
+  return {
+    expiry,
+    pda,
+    ...fixture,
+  };
+};
+
 describe("solana-options", { timeout: 100_000 }, () => {
   describe("initialize instruction", () => {
     it("Can initialize option", async () => {
@@ -266,12 +638,7 @@ describe("solana-options", { timeout: 100_000 }, () => {
       ).to.equal(BigInt(1000));
 
       const pda = getPda({
-        amountQuote: 42n,
-        amountBase: 1000n,
-        buyer: buyer.publicKey,
-        expiry: BigInt(expiry.toString()),
-        mintQuote: usdc,
-        mintBase: wsol,
+        nonce: 0n,
         programId: program.programId,
         seller: seller.publicKey,
       });
@@ -281,14 +648,23 @@ describe("solana-options", { timeout: 100_000 }, () => {
         .initialize(
           new anchor.BN("1000"),
           new anchor.BN("42"),
-          new anchor.BN(expiry)
+          new anchor.BN(expiry),
+          new anchor.BN(10),
+          new anchor.BN(0),
+          { cash: {} },
+          { european: {} },
+          [],
+          { spot: {} }
         )
         .accounts({
           buyer: buyer.publicKey,
           mintQuote: usdc,
           mintBase: wsol,
+          mintPremium: wsol,
           seller: seller.publicKey,
           // data: pda,
+          tokenProgram: token.TOKEN_PROGRAM_ID,
+          tokenProgramPremium: token.TOKEN_PROGRAM_ID,
         })
         .signers([seller])
         .rpc();
@@ -297,13 +673,24 @@ describe("solana-options", { timeout: 100_000 }, () => {
       expect(await program.account.coveredCall.fetch(pda)).toStrictEqual({
         amountBase: expect.toBeBN(new anchor.BN(1000)),
         amountPremium: null,
+        amountPremiumAsk: expect.toBeBN(new BN(10)),
         amountQuote: expect.toBeBN(new anchor.BN(42)),
         bump: expect.any(Number),
+        creator: seller.publicKey,
+        decimalsBase: 9,
+        decimalsQuote: 6,
+        feedId: SOL_FEED_ID,
         buyer: buyer.publicKey,
         isExercised: false,
         mintBase: wsol,
+        mintPremium: wsol,
         mintQuote: usdc,
+        nonce: expect.toBeBN(new BN(0)),
         seller: seller.publicKey,
+        settlement: { cash: {} },
+        style: { european: {} },
+        exerciseDates: [],
+        priceKind: { spot: {} },
         timestampExpiry: expect.toBeBN(expiry),
         timestampCreated: expect.any(BN),
       });
@@ -329,12 +716,21 @@ describe("solana-options", { timeout: 100_000 }, () => {
         .initialize(
           new anchor.BN("500"),
           new anchor.BN(1),
-          new anchor.BN(expiry)
+          new anchor.BN(expiry),
+          new anchor.BN(10),
+          new anchor.BN(0),
+          { cash: {} },
+          { european: {} },
+          [],
+          { spot: {} }
         )
         .accounts({
           mintBase: wsol,
+          mintPremium: wsol,
           mintQuote: usdc,
           buyer: buyer.publicKey,
+          tokenProgram: token.TOKEN_PROGRAM_ID,
+          tokenProgramPremium: token.TOKEN_PROGRAM_ID,
         })
         .rpc();
 
@@ -342,12 +738,21 @@ describe("solana-options", { timeout: 100_000 }, () => {
         .initialize(
           new anchor.BN("500"),
           new anchor.BN(1),
-          new anchor.BN(expiry)
+          new anchor.BN(expiry),
+          new anchor.BN(10),
+          new anchor.BN(0),
+          { cash: {} },
+          { european: {} },
+          [],
+          { spot: {} }
         )
         .accounts({
           mintBase: wsol,
+          mintPremium: wsol,
           mintQuote: usdc,
           buyer: buyer.publicKey,
+          tokenProgram: token.TOKEN_PROGRAM_ID,
+          tokenProgramPremium: token.TOKEN_PROGRAM_ID,
         })
         .rpc();
     });
@@ -365,12 +770,21 @@ describe("solana-options", { timeout: 100_000 }, () => {
           .initialize(
             new anchor.BN("1000"),
             new anchor.BN(1),
-            new anchor.BN(Math.floor(Date.now() / 1000) - 600)
+            new anchor.BN(Math.floor(Date.now() / 1000) - 600),
+            new anchor.BN(10),
+            new anchor.BN(0),
+            { cash: {} },
+            { european: {} },
+            [],
+            { spot: {} }
           )
           .accounts({
             mintBase: wsol,
+            mintPremium: wsol,
             mintQuote: usdc,
             buyer: buyer.publicKey,
+            tokenProgram: token.TOKEN_PROGRAM_ID,
+            tokenProgramPremium: token.TOKEN_PROGRAM_ID,
           })
           .rpc()
       ).rejects.toThrowError(
@@ -390,21 +804,130 @@ describe("solana-options", { timeout: 100_000 }, () => {
           .initialize(
             new anchor.BN("10000"),
             new anchor.BN(1),
-            new anchor.BN(Math.floor(Date.now() / 1000) + 60)
+            new anchor.BN(Math.floor(Date.now() / 1000) + 60),
+            new anchor.BN(10),
+            new anchor.BN(0),
+            { cash: {} },
+            { european: {} },
+            [],
+            { spot: {} }
           )
           .accounts({
             mintBase: wsol,
+            mintPremium: wsol,
             mintQuote: usdc,
             buyer: buyer.publicKey,
+            tokenProgram: token.TOKEN_PROGRAM_ID,
+            tokenProgramPremium: token.TOKEN_PROGRAM_ID,
           })
           .rpc()
       ).rejects.toThrowError(
         "AnchorError caused by account: ata_seller_base. Error Code: ConstraintRaw. Error Number: 2003. Error Message: A raw constraint was violated."
       );
     });
+    it("Can initialize open offer without buyer", async () => {
+      const { program, pda } = await fixtureOpenOffer();
+
+      const data = await program.account.coveredCall.fetch(pda);
+      expect(data.buyer).toStrictEqual(PublicKey.default);
+      expect(data.nonce).toBeBN(new BN(1));
+    });
+    it("Can reject initialize with unrepresentable strike", async () => {
+      const { program, wsol, usdc, buyer } = await fixtureDeployed();
+
+      await expect(
+        program.methods
+          .initialize(
+            new anchor.BN(1),
+            new anchor.BN("18446744073709551615"),
+            new anchor.BN(Math.floor(Date.now() / 1000) + 60),
+            new anchor.BN(10),
+            new anchor.BN(0),
+            { cash: {} },
+            { european: {} },
+            [],
+            { spot: {} }
+          )
+          .accounts({
+            mintBase: wsol,
+            mintPremium: wsol,
+            mintQuote: usdc,
+            buyer: buyer.publicKey,
+            tokenProgram: token.TOKEN_PROGRAM_ID,
+            tokenProgramPremium: token.TOKEN_PROGRAM_ID,
+          })
+          .rpc()
+      ).rejects.toThrowError(
+        /Error Code: MathOverflow. Error Number: 6012. Error Message: Math overflow./
+      );
+    });
   });
 
   describe("Buy instruction", () => {
+    it("Can allow anyone to buy an open offer", async () => {
+      const { program, pda, seller, buyer, wsol, context } =
+        await fixtureOpenOffer();
+
+      await program.methods
+        .buy(new anchor.BN(10))
+        .accounts({
+          data: pda,
+          seller: seller.publicKey,
+          buyer: buyer.publicKey,
+          mintPremium: wsol,
+          payer: buyer.publicKey,
+          tokenProgram: token.TOKEN_PROGRAM_ID,
+        })
+        .signers([buyer])
+        .rpc();
+
+      const data = await program.account.coveredCall.fetch(pda);
+      expect(data.buyer).toStrictEqual(buyer.publicKey);
+      expect(data.amountPremium).toBeBN(new BN(10));
+      expect(
+        await getAtaTokenBalance(context.banksClient, wsol, buyer.publicKey)
+      ).to.equal(BigInt(990));
+    });
+
+    it("Can reject a second buyer of an open offer", async () => {
+      const { program, pda, seller, buyer, wsol, context } =
+        await fixtureOpenOffer();
+
+      await program.methods
+        .buy(new anchor.BN(10))
+        .accounts({
+          data: pda,
+          seller: seller.publicKey,
+          buyer: buyer.publicKey,
+          mintPremium: wsol,
+          payer: buyer.publicKey,
+          tokenProgram: token.TOKEN_PROGRAM_ID,
+        })
+        .signers([buyer])
+        .rpc();
+
+      const keeper = Keypair.generate();
+      await airdrop(context, keeper.publicKey, 1 * LAMPORTS_PER_SOL);
+      await fundAtaAccount(context.banksClient, wsol, keeper, BigInt(1000));
+
+      await expect(
+        program.methods
+          .buy(new anchor.BN(10))
+          .accounts({
+            data: pda,
+            seller: seller.publicKey,
+            buyer: keeper.publicKey,
+            mintPremium: wsol,
+            payer: keeper.publicKey,
+            tokenProgram: token.TOKEN_PROGRAM_ID,
+          })
+          .signers([keeper])
+          .rpc()
+      ).rejects.toThrowError(
+        "AnchorError caused by account: buyer. Error Code: ConstraintRaw. Error Number: 2003. Error Message: A raw constraint was violated"
+      );
+    });
+
     it("Can allow buyer to successfully buy ", async () => {
       const { program, pda, buyer, wsol, context, expiry, usdc, seller } =
         await fixtureInitialized();
@@ -417,9 +940,11 @@ describe("solana-options", { timeout: 100_000 }, () => {
         .buy(new anchor.BN(10))
         .accounts({
           data: pda,
+          seller: seller.publicKey,
           buyer: buyer.publicKey,
           mintPremium: wsol,
           payer: buyer.publicKey,
+          tokenProgram: token.TOKEN_PROGRAM_ID,
         })
         .signers([buyer])
         .rpc();
@@ -428,13 +953,24 @@ describe("solana-options", { timeout: 100_000 }, () => {
       expect(await program.account.coveredCall.fetch(pda)).toStrictEqual({
         amountBase: expect.toBeBN(new BN(1000)),
         amountPremium: expect.toBeBN(new BN(10)),
+        amountPremiumAsk: expect.toBeBN(new BN(10)),
         amountQuote: expect.toBeBN(new BN(3500)),
         bump: expect.any(Number),
+        creator: seller.publicKey,
+        decimalsBase: 9,
+        decimalsQuote: 6,
+        feedId: SOL_FEED_ID,
         buyer: buyer.publicKey,
         isExercised: false,
         mintBase: wsol,
+        mintPremium: wsol,
         mintQuote: usdc,
+        nonce: expect.toBeBN(new BN(0)),
         seller: seller.publicKey,
+        settlement: { cash: {} },
+        style: { european: {} },
+        exerciseDates: [],
+        priceKind: { spot: {} },
         timestampCreated: expect.any(BN),
         timestampExpiry: expect.toBeBN(expiry),
       });
@@ -444,8 +980,11 @@ describe("solana-options", { timeout: 100_000 }, () => {
       ).to.equal(BigInt(990));
 
       expect(await getAtaTokenBalance(context.banksClient, wsol, pda)).to.equal(
-        BigInt(1010)
+        BigInt(1000)
       );
+      expect(
+        await getAtaTokenBalance(context.banksClient, wsol, seller.publicKey)
+      ).to.equal(BigInt(10));
     });
 
     it("Can allow 3rd party to successfully buy for buyer", async () => {
@@ -459,9 +998,11 @@ describe("solana-options", { timeout: 100_000 }, () => {
           .buy(new anchor.BN(10))
           .accounts({
             data: pda,
+            seller: seller.publicKey,
             buyer: buyer.publicKey,
             mintPremium: wsol,
             payer: keeper.publicKey,
+            tokenProgram: token.TOKEN_PROGRAM_ID,
           })
           .signers([keeper])
           .rpc();
@@ -470,13 +1011,24 @@ describe("solana-options", { timeout: 100_000 }, () => {
       expect(await program.account.coveredCall.fetch(pda)).toStrictEqual({
         amountBase: expect.toBeBN(new BN(1000)),
         amountPremium: expect.toBeBN(new BN(10)),
+        amountPremiumAsk: expect.toBeBN(new BN(10)),
         amountQuote: expect.toBeBN(new BN(3500)),
         bump: expect.any(Number),
+        creator: seller.publicKey,
+        decimalsBase: 9,
+        decimalsQuote: 6,
+        feedId: SOL_FEED_ID,
         buyer: buyer.publicKey,
         isExercised: false,
         mintBase: wsol,
+        mintPremium: wsol,
         mintQuote: usdc,
+        nonce: expect.toBeBN(new BN(0)),
         seller: seller.publicKey,
+        settlement: { cash: {} },
+        style: { european: {} },
+        exerciseDates: [],
+        priceKind: { spot: {} },
         timestampCreated: expect.any(BN),
         timestampExpiry: expect.toBeBN(expiry),
       });
@@ -486,21 +1038,87 @@ describe("solana-options", { timeout: 100_000 }, () => {
       ).to.equal(BigInt(990));
 
       expect(await getAtaTokenBalance(context.banksClient, wsol, pda)).to.equal(
-        BigInt(1010)
+        BigInt(1000)
+      );
+      expect(
+        await getAtaTokenBalance(context.banksClient, wsol, seller.publicKey)
+      ).to.equal(BigInt(10));
+    });
+
+    it("Can allow premium to be paid in the quote mint", async () => {
+      const { program, seller, buyer, wsol, usdc, context } =
+        await fixtureDeployed();
+      const expiry = new anchor.BN(Math.floor(Date.now() / 1000) + 180);
+
+      await program.methods
+        .initialize(
+          new anchor.BN("1000"),
+          new anchor.BN("3500"),
+          new anchor.BN(expiry),
+          new anchor.BN(10),
+          new anchor.BN(3),
+          { cash: {} },
+          { european: {} },
+          [],
+          { spot: {} }
+        )
+        .accounts({
+          mintBase: wsol,
+          mintPremium: usdc,
+          mintQuote: usdc,
+          buyer: buyer.publicKey,
+          tokenProgram: token.TOKEN_PROGRAM_ID,
+          tokenProgramPremium: token.TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+      const pda = getPda({
+        nonce: 3n,
+        programId: program.programId,
+        seller: seller.publicKey,
+      });
+      expect(
+        (await program.account.coveredCall.fetch(pda)).mintPremium
+      ).toStrictEqual(usdc);
+
+      await fundAtaAccount(context.banksClient, usdc, buyer, BigInt(10));
+      await program.methods
+        .buy(new anchor.BN(10))
+        .accounts({
+          data: pda,
+          seller: seller.publicKey,
+          buyer: buyer.publicKey,
+          mintPremium: usdc,
+          payer: buyer.publicKey,
+          tokenProgram: token.TOKEN_PROGRAM_ID,
+        })
+        .signers([buyer])
+        .rpc();
+
+      expect(
+        await getAtaTokenBalance(context.banksClient, usdc, buyer.publicKey)
+      ).to.equal(BigInt(0));
+      expect(
+        await getAtaTokenBalance(context.banksClient, usdc, seller.publicKey)
+      ).to.equal(BigInt(10));
+      expect(await getAtaTokenBalance(context.banksClient, wsol, pda)).to.equal(
+        BigInt(1000)
       );
     });
 
     it("Can reject if option has already been bought", async () => {
-      const { program, pda, buyer, wsol, context, expiry } =
+      const { program, pda, seller, buyer, wsol, context, expiry } =
         await fixtureInitialized();
 
       await program.methods
         .buy(new anchor.BN(10))
         .accounts({
           data: pda,
+          seller: seller.publicKey,
           buyer: buyer.publicKey,
           mintPremium: wsol,
           payer: buyer.publicKey,
+          tokenProgram: token.TOKEN_PROGRAM_ID,
         })
         .signers([buyer])
         .rpc();
@@ -514,8 +1132,10 @@ describe("solana-options", { timeout: 100_000 }, () => {
           .accounts({
             buyer: buyer.publicKey,
             data: pda,
+            seller: seller.publicKey,
             mintPremium: wsol,
             payer: buyer.publicKey,
+            tokenProgram: token.TOKEN_PROGRAM_ID,
           })
           .signers([buyer])
           .rpc()
@@ -525,7 +1145,7 @@ describe("solana-options", { timeout: 100_000 }, () => {
     });
 
     it("Can reject if option is expired", async () => {
-      const { program, pda, buyer, wsol, context, expiry } =
+      const { program, pda, seller, buyer, wsol, context, expiry } =
         await fixtureInitialized();
 
       // Lets warp past the expiry
@@ -537,8 +1157,10 @@ describe("solana-options", { timeout: 100_000 }, () => {
           .accounts({
             payer: buyer.publicKey,
             data: pda,
+            seller: seller.publicKey,
             buyer: buyer.publicKey,
             mintPremium: wsol,
+            tokenProgram: token.TOKEN_PROGRAM_ID,
           })
           .signers([buyer])
           .rpc()
@@ -547,70 +1169,79 @@ describe("solana-options", { timeout: 100_000 }, () => {
       );
     });
 
-    it("Can reject buy if premium is not in base", async () => {
-      const { program, pda, seller, buyer, wsol, context, usdc } =
+    it("Can reject buy if premium is in a different mint", async () => {
+      const { program, pda, seller, buyer, context, usdc } =
         await fixtureInitialized();
 
       await fundAtaAccount(context.banksClient, usdc, buyer, BigInt(500));
 
-      expect(
-        await getAtaTokenBalance(context.banksClient, usdc, buyer.publicKey)
-      ).to.equal(BigInt(500));
-
       await expect(
         program.methods
           .buy(new anchor.BN(500))
           .accounts({
             payer: buyer.publicKey,
             data: pda,
+            seller: seller.publicKey,
             buyer: buyer.publicKey,
             mintPremium: usdc,
+            tokenProgram: token.TOKEN_PROGRAM_ID,
           })
           .signers([buyer])
           .rpc()
       ).rejects.toThrowError(
-        "AnchorError caused by account: ata_vault_premium. Error Code: AccountNotInitialized. Error Number: 3012. Error Message: The program expected this account to be already initialized."
+        "AnchorError caused by account: mint_premium. Error Code: ConstraintRaw. Error Number: 2003. Error Message: A raw constraint was violated."
       );
+    });
 
-      // Also test when ata is initialized
-      await createAssociatedTokenAccount(context.banksClient, buyer, usdc, pda);
+    it("Can reject if buyer has insufficient funds", async () => {
+      const { program, pda, seller, buyer, wsol, context } =
+        await fixtureInitialized();
 
+      expect(
+        await getAtaTokenBalance(context.banksClient, wsol, buyer.publicKey)
+      ).to.equal(BigInt(1000));
       await expect(
         program.methods
-          .buy(new anchor.BN(500))
+          .buy(new anchor.BN(2000))
           .accounts({
             payer: buyer.publicKey,
             data: pda,
+            seller: seller.publicKey,
             buyer: buyer.publicKey,
-            mintPremium: usdc,
+            mintPremium: wsol,
+            tokenProgram: token.TOKEN_PROGRAM_ID,
           })
           .signers([buyer])
           .rpc()
       ).rejects.toThrowError(
-        "AnchorError caused by account: mint_premium. Error Code: ConstraintRaw. Error Number: 2003. Error Message: A raw constraint was violated."
+        "AnchorError caused by account: ata_payer_premium. Error Code: ConstraintRaw. Error Number: 2003. Error Message: A raw constraint was violated."
       );
     });
 
-    it("Can reject if buyer has insufficient funds", async () => {
-      const { program, pda, buyer, wsol, context } = await fixtureInitialized();
+    it("Can reject if premium is below asking price", async () => {
+      const { program, pda, seller, buyer, wsol, context } =
+        await fixtureInitialized();
 
-      expect(
-        await getAtaTokenBalance(context.banksClient, wsol, buyer.publicKey)
-      ).to.equal(BigInt(1000));
       await expect(
         program.methods
-          .buy(new anchor.BN(2000))
+          .buy(new anchor.BN(9))
           .accounts({
             payer: buyer.publicKey,
             data: pda,
+            seller: seller.publicKey,
             buyer: buyer.publicKey,
             mintPremium: wsol,
+            tokenProgram: token.TOKEN_PROGRAM_ID,
           })
           .signers([buyer])
           .rpc()
       ).rejects.toThrowError(
-        "AnchorError caused by account: ata_payer_premium. Error Code: ConstraintRaw. Error Number: 2003. Error Message: A raw constraint was violated."
+        /AnchorError thrown in programs\/solana-options\/src\/instructions\/buy.rs:\d\d. Error Code: PremiumTooLow. Error Number: 6009. Error Message: Premium is below the asking price./
       );
+
+      expect(
+        await getAtaTokenBalance(context.banksClient, wsol, buyer.publicKey)
+      ).to.equal(BigInt(1000));
     });
 
     it("Can reject if not buyer", async () => {
@@ -622,8 +1253,10 @@ describe("solana-options", { timeout: 100_000 }, () => {
           .accounts({
             payer: seller.publicKey,
             data: pda,
+            seller: seller.publicKey,
             buyer: seller.publicKey,
             mintPremium: wsol,
+            tokenProgram: token.TOKEN_PROGRAM_ID,
           })
           .signers([seller])
           .rpc()
@@ -646,6 +1279,7 @@ describe("solana-options", { timeout: 100_000 }, () => {
             mintQuote: usdc,
             data: pda,
             buyer: buyer.publicKey,
+            tokenProgram: token.TOKEN_PROGRAM_ID,
           })
           .signers([buyer])
           .rpc()
@@ -656,9 +1290,12 @@ describe("solana-options", { timeout: 100_000 }, () => {
 
     const fixtureMarked = async () => {
       const fixture = await fixtureBought();
-      const { program, setPrice, expiry } = fixture;
+      const { program, setPrice, expiry, market } = fixture;
       setPrice(4000);
-      await program.methods.mark(expiry).accounts({ priceUpdate }).rpc();
+      await program.methods
+        .mark(expiry)
+        .accounts({ priceUpdate, market })
+        .rpc();
       return fixture;
     };
 
@@ -672,7 +1309,7 @@ describe("solana-options", { timeout: 100_000 }, () => {
 
       expect(getStrikePrice(1000n, 3500n)).to.equal(3500);
 
-      await warpTo(context, expiry.add(new anchor.BN(100)));
+      await warpTo(context, expiry.add(MARK_GRACE_PERIOD));
       await program.methods
         .exercise()
         .accounts({
@@ -680,6 +1317,7 @@ describe("solana-options", { timeout: 100_000 }, () => {
           mintQuote: usdc,
           data: pda,
           buyer: buyer.publicKey,
+          tokenProgram: token.TOKEN_PROGRAM_ID,
         })
         .signers([buyer])
         .rpc();
@@ -688,7 +1326,7 @@ describe("solana-options", { timeout: 100_000 }, () => {
         await getAtaTokenBalance(context.banksClient, wsol, buyer.publicKey)
       ).to.equal(BigInt(990 + 125));
       expect(await getAtaTokenBalance(context.banksClient, wsol, pda)).to.equal(
-        BigInt(1000 - 125 + 10)
+        BigInt(1000 - 125)
       );
     });
 
@@ -696,7 +1334,7 @@ describe("solana-options", { timeout: 100_000 }, () => {
       const { program, pda, buyer, wsol, context, usdc, expiry } =
         await fixtureMarked();
 
-      await warpTo(context, expiry.add(new anchor.BN(100)));
+      await warpTo(context, expiry.add(MARK_GRACE_PERIOD));
       await program.methods
         .exercise()
         .accounts({
@@ -704,6 +1342,7 @@ describe("solana-options", { timeout: 100_000 }, () => {
           mintQuote: usdc,
           data: pda,
           buyer: buyer.publicKey,
+          tokenProgram: token.TOKEN_PROGRAM_ID,
         })
         .signers([buyer])
         .rpc();
@@ -719,6 +1358,7 @@ describe("solana-options", { timeout: 100_000 }, () => {
             mintQuote: usdc,
             data: pda,
             buyer: buyer.publicKey,
+            tokenProgram: token.TOKEN_PROGRAM_ID,
           })
           .signers([buyer])
           .rpc()
@@ -728,13 +1368,25 @@ describe("solana-options", { timeout: 100_000 }, () => {
     });
 
     it("Can reject if option hasn't been bought", async () => {
-      const { program, pda, buyer, wsol, context, usdc, setPrice, expiry } =
-        await fixtureInitialized();
+      const {
+        program,
+        pda,
+        buyer,
+        wsol,
+        context,
+        usdc,
+        setPrice,
+        expiry,
+        market,
+      } = await fixtureInitialized();
 
       setPrice(4000);
-      await program.methods.mark(expiry).accounts({ priceUpdate }).rpc();
+      await program.methods
+        .mark(expiry)
+        .accounts({ priceUpdate, market })
+        .rpc();
 
-      await warpTo(context, expiry.add(new anchor.BN(100)));
+      await warpTo(context, expiry.add(MARK_GRACE_PERIOD));
 
       await expect(
         program.methods
@@ -744,6 +1396,7 @@ describe("solana-options", { timeout: 100_000 }, () => {
             mintQuote: usdc,
             data: pda,
             buyer: buyer.publicKey,
+            tokenProgram: token.TOKEN_PROGRAM_ID,
           })
           .signers([buyer])
           .rpc()
@@ -764,6 +1417,7 @@ describe("solana-options", { timeout: 100_000 }, () => {
             mintQuote: usdc,
             data: pda,
             buyer: seller.publicKey,
+            tokenProgram: token.TOKEN_PROGRAM_ID,
           })
           .signers([seller])
           .rpc()
@@ -784,6 +1438,7 @@ describe("solana-options", { timeout: 100_000 }, () => {
             mintQuote: usdc,
             data: pda,
             buyer: buyer.publicKey,
+            tokenProgram: token.TOKEN_PROGRAM_ID,
           })
           .signers([buyer])
           .rpc()
@@ -793,206 +1448,2445 @@ describe("solana-options", { timeout: 100_000 }, () => {
     });
   });
 
-  describe("Close instruction", () => {
-    it("Can successfully close exercised option by seller", async () => {
-      const { program, pda, buyer, wsol, context, usdc, seller } =
-        await fixtureExercised();
+  describe("American exercise", () => {
+    const fixtureAmericanBought = async () => {
+      const fixture = await fixtureDeployed();
+      const { context, program, wsol, usdc, buyer, seller } = fixture;
+      const expiry = new anchor.BN(Math.floor(Date.now() / 1000) + 180);
+      await program.methods
+        .initialize(
+          new anchor.BN("1000"),
+          new anchor.BN("3500"),
+          new anchor.BN(expiry),
+          new anchor.BN(10),
+          new anchor.BN(4),
+          { cash: {} },
+          { american: {} },
+          [],
+          { spot: {} }
+        )
+        .accounts({
+          mintBase: wsol,
+          mintPremium: wsol,
+          mintQuote: usdc,
+          buyer: buyer.publicKey,
+          tokenProgram: token.TOKEN_PROGRAM_ID,
+          tokenProgramPremium: token.TOKEN_PROGRAM_ID,
+        })
+        .rpc();
 
-      expect(await getAtaTokenBalance(context.banksClient, wsol, pda)).to.equal(
-        BigInt(885)
-      );
-      expect(
-        await getAtaTokenBalance(context.banksClient, wsol, seller.publicKey)
-      ).to.equal(BigInt(0));
+      const pda = getPda({
+        nonce: 4n,
+        programId: program.programId,
+        seller: seller.publicKey,
+      });
 
       await program.methods
-        .close()
+        .buy(new anchor.BN(10))
         .accounts({
-          mintBase: wsol,
           data: pda,
           seller: seller.publicKey,
           buyer: buyer.publicKey,
+          mintPremium: wsol,
+          payer: buyer.publicKey,
+          tokenProgram: token.TOKEN_PROGRAM_ID,
         })
-        .signers([seller])
+        .signers([buyer])
         .rpc();
 
-      // Expect accounts to be close
-      expect(await context.banksClient.getAccount(pda)).to.equal(null);
-      expect(
-        await context.banksClient.getAccount(
-          token.getAssociatedTokenAddressSync(wsol, pda, true)
-        )
-      ).to.equal(null);
-      expect(
-        await context.banksClient.getAccount(
-          token.getAssociatedTokenAddressSync(usdc, pda, true)
-        )
-      ).to.equal(null);
-
-      expect(await getAtaTokenBalance(context.banksClient, wsol, pda)).to.equal(
-        BigInt(0)
-      );
-      expect(
-        await getAtaTokenBalance(context.banksClient, wsol, seller.publicKey)
-      ).to.equal(BigInt(885));
-    });
-
-    it("Can successfully close exercised option by anyone", async () => {
-      const { program, pda, buyer, wsol, context, usdc, seller, payer } =
-        await fixtureExercised();
-
-      const keeper = Keypair.generate();
-      await airdrop(context, keeper.publicKey, 1 * LAMPORTS_PER_SOL);
+      // Pin the clock so price publish times can be set relative to it
+      const now = Math.floor(Date.now() / 1000);
+      await warpTo(context, new anchor.BN(now));
 
-      expect(await getAtaTokenBalance(context.banksClient, wsol, pda)).to.equal(
-        BigInt(885)
-      );
-      expect(
-        await getAtaTokenBalance(context.banksClient, wsol, seller.publicKey)
-      ).to.equal(BigInt(0));
+      return { ...fixture, expiry, pda, now: now + 100 };
+    };
 
-      await fundAtaAccount(context.banksClient, usdc, seller, 0);
+    it("Can exercise before expiry at the live price", async () => {
+      const { program, pda, buyer, wsol, context, setPrice, now } =
+        await fixtureAmericanBought();
 
+      setPrice(4000, new Date(now * 1000));
       await program.methods
-        .close()
+        .exerciseEarly()
         .accounts({
           mintBase: wsol,
-          mintQuote: usdc,
           data: pda,
-          seller: seller.publicKey,
-          payer: keeper.publicKey,
           buyer: buyer.publicKey,
+          priceUpdate,
+          tokenProgram: token.TOKEN_PROGRAM_ID,
         })
-        .signers([keeper])
+        .signers([buyer])
         .rpc();
 
+      expect(
+        await getAtaTokenBalance(context.banksClient, wsol, buyer.publicKey)
+      ).to.equal(BigInt(990 + 125));
       expect(await getAtaTokenBalance(context.banksClient, wsol, pda)).to.equal(
-        BigInt(0)
+        BigInt(1000 - 125)
       );
-      expect(
-        await getAtaTokenBalance(context.banksClient, wsol, seller.publicKey)
-      ).to.equal(BigInt(885));
     });
 
-    it("Can successfully close unbought option by anyone", async () => {
-      const { program, pda, buyer, wsol, context, usdc, seller } =
-        await fixtureInitialized();
-
-      const keeper = Keypair.generate();
-      await airdrop(context, keeper.publicKey, 1 * LAMPORTS_PER_SOL);
-
-      expect(await getAtaTokenBalance(context.banksClient, wsol, pda)).to.equal(
-        BigInt(1000)
-      );
-      expect(
-        await getAtaTokenBalance(context.banksClient, wsol, seller.publicKey)
-      ).to.equal(BigInt(0));
+    it("Can let seller close straight after early exercise", async () => {
+      const { program, pda, buyer, wsol, context, setPrice, now, seller } =
+        await fixtureAmericanBought();
 
+      setPrice(4000, new Date(now * 1000));
       await program.methods
-        .close()
+        .exerciseEarly()
         .accounts({
           mintBase: wsol,
           data: pda,
-          seller: seller.publicKey,
-          payer: keeper.publicKey,
           buyer: buyer.publicKey,
-          expiry: null,
+          priceUpdate,
+          tokenProgram: token.TOKEN_PROGRAM_ID,
         })
-        .signers([keeper])
+        .signers([buyer])
         .rpc();
 
-      expect(await getAtaTokenBalance(context.banksClient, wsol, pda)).to.equal(
-        BigInt(0)
-      );
-      expect(
-        await getAtaTokenBalance(context.banksClient, wsol, seller.publicKey)
-      ).to.equal(BigInt(1000));
-    });
-
-    it("Can successfully close unexercised option after expiry", async () => {
-      const { program, pda, wsol, context, seller, expiry, buyer, setPrice } =
-        await fixtureBought();
-
-      await warpTo(context, expiry.add(new anchor.BN(100)));
-
-      expect(await getAtaTokenBalance(context.banksClient, wsol, pda)).to.equal(
-        BigInt(1010)
-      );
-      expect(
-        await getAtaTokenBalance(context.banksClient, wsol, seller.publicKey)
-      ).to.equal(BigInt(0));
-
-      setPrice(3000);
-      await program.methods.mark(expiry).accounts({ priceUpdate }).rpc();
-
       await program.methods
         .close()
         .accounts({
           mintBase: wsol,
           data: pda,
           seller: seller.publicKey,
-          payer: seller.publicKey,
-          buyer: buyer.publicKey,
+          tokenProgram: token.TOKEN_PROGRAM_ID,
         })
         .signers([seller])
         .rpc();
 
-      // Expect accounts to be close
       expect(await context.banksClient.getAccount(pda)).to.equal(null);
-      expect(
-        await context.banksClient.getAccount(
-          token.getAssociatedTokenAddressSync(wsol, pda, true)
-        )
-      ).to.equal(null);
-
-      expect(await getAtaTokenBalance(context.banksClient, wsol, pda)).to.equal(
-        BigInt(0)
-      );
       expect(
         await getAtaTokenBalance(context.banksClient, wsol, seller.publicKey)
-      ).to.equal(BigInt(1010));
+      ).to.equal(BigInt(10 + 875));
     });
 
-    it("Can reject closing bought option before expiry", async () => {
-      const {
-        program,
-        pda,
-        wsol,
-        context,
-        usdc,
-        seller,
-        buyer,
-        expiry,
-        setPrice,
-      } = await fixtureBought();
+    it("Can reject a stale price", async () => {
+      const { program, pda, buyer, wsol, setPrice, now } =
+        await fixtureAmericanBought();
 
+      setPrice(4000, new Date((now - 60) * 1000));
       await expect(
         program.methods
-          .close()
+          .exerciseEarly()
           .accounts({
-            buyer: buyer.publicKey,
-            data: pda,
-            mintQuote: usdc,
             mintBase: wsol,
-            payer: seller.publicKey,
-            seller: seller.publicKey,
-            expiry: null,
+            data: pda,
+            buyer: buyer.publicKey,
+            priceUpdate,
+            tokenProgram: token.TOKEN_PROGRAM_ID,
           })
-          .signers([seller])
+          .signers([buyer])
           .rpc()
-      ).rejects.toThrowError(
-        /AnchorError thrown in programs\/solana-options\/src\/instructions\/close.rs:\d\d. Error Code: OptionCannotBeClosedYet. Error Number: 6004. Error Message: Option cannot be closed Yet./
-      );
+      ).rejects.toThrowError(/PriceTooOld/);
     });
-  });
-
-  it("has correct price update account", async () => {
-    const { provider } = await fixtureDeployed();
 
-    const SOL_PRICE_FEED_ID =
-      "0xef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d";
+    it("Can reject if out of the money", async () => {
+      const { program, pda, buyer, wsol, setPrice, now } =
+        await fixtureAmericanBought();
 
-    const pyth = new PythSolanaReceiver(provider);
+      setPrice(3000, new Date(now * 1000));
+      await expect(
+        program.methods
+          .exerciseEarly()
+          .accounts({
+            mintBase: wsol,
+            data: pda,
+            buyer: buyer.publicKey,
+            priceUpdate,
+            tokenProgram: token.TOKEN_PROGRAM_ID,
+          })
+          .signers([buyer])
+          .rpc()
+      ).rejects.toThrowError(
+        /AnchorError thrown in programs\/solana-options\/src\/instructions\/exercise_early.rs:\d+. Error Code: OptionOutOfTheMoney. Error Number: 6016. Error Message: Option is out of the money./
+      );
+    });
+
+    it("Can reject early exercise of a European option", async () => {
+      const { program, pda, buyer, wsol, setPrice } = await fixtureBought();
+
+      setPrice(4000);
+      await expect(
+        program.methods
+          .exerciseEarly()
+          .accounts({
+            mintBase: wsol,
+            data: pda,
+            buyer: buyer.publicKey,
+            priceUpdate,
+            tokenProgram: token.TOKEN_PROGRAM_ID,
+          })
+          .signers([buyer])
+          .rpc()
+      ).rejects.toThrowError(
+        /AnchorError thrown in programs\/solana-options\/src\/instructions\/exercise_early.rs:\d\d. Error Code: InvalidExerciseStyle. Error Number: 6015. Error Message: Option uses a different exercise style./
+      );
+    });
+  });
+
+  describe("Bermudan exercise", () => {
+    const fixtureBermudan = async (offsets: number[]) => {
+      const fixture = await fixtureDeployed();
+      const { program, wsol, usdc, buyer, seller } = fixture;
+      const now = Math.floor(Date.now() / 1000);
+      const expiry = new anchor.BN(now + 1200);
+      const dates = offsets.map((x) => new anchor.BN(now + x));
+      await program.methods
+        .initialize(
+          new anchor.BN("1000"),
+          new anchor.BN("3500"),
+          expiry,
+          new anchor.BN(10),
+          new anchor.BN(5),
+          { cash: {} },
+          { bermudan: {} },
+          dates,
+          { spot: {} }
+        )
+        .accounts({
+          mintBase: wsol,
+          mintPremium: wsol,
+          mintQuote: usdc,
+          buyer: buyer.publicKey,
+          tokenProgram: token.TOKEN_PROGRAM_ID,
+          tokenProgramPremium: token.TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+      const pda = getPda({
+        nonce: 5n,
+        programId: program.programId,
+        seller: seller.publicKey,
+      });
+
+      await program.methods
+        .buy(new anchor.BN(10))
+        .accounts({
+          data: pda,
+          seller: seller.publicKey,
+          buyer: buyer.publicKey,
+          mintPremium: wsol,
+          payer: buyer.publicKey,
+          tokenProgram: token.TOKEN_PROGRAM_ID,
+        })
+        .signers([buyer])
+        .rpc();
+
+      return { ...fixture, expiry, pda, dates };
+    };
+
+    it("Can exercise on a scheduled date", async () => {
+      const { program, pda, buyer, wsol, context, setPrice, dates, market } =
+        await fixtureBermudan([30]);
+
+      setPrice(4000, new Date(dates[0].toNumber() * 1000 - 1000));
+      await program.methods
+        .mark(dates[0])
+        .accounts({ priceUpdate, market })
+        .rpc();
+
+      await warpTo(context, dates[0].add(MARK_GRACE_PERIOD));
+      await program.methods
+        .exerciseScheduled(dates[0])
+        .accounts({
+          mintBase: wsol,
+          data: pda,
+          buyer: buyer.publicKey,
+          tokenProgram: token.TOKEN_PROGRAM_ID,
+        })
+        .signers([buyer])
+        .rpc();
+
+      expect(
+        await getAtaTokenBalance(context.banksClient, wsol, buyer.publicKey)
+      ).to.equal(BigInt(990 + 125));
+      expect(await getAtaTokenBalance(context.banksClient, wsol, pda)).to.equal(
+        BigInt(1000 - 125)
+      );
+    });
+
+    it("Can reject a date superseded by a later one", async () => {
+      const { program, pda, buyer, wsol, context, setPrice, dates, market } =
+        await fixtureBermudan([30, 400]);
+
+      setPrice(4000, new Date(dates[0].toNumber() * 1000 - 1000));
+      await program.methods
+        .mark(dates[0])
+        .accounts({ priceUpdate, market })
+        .rpc();
+
+      await warpTo(context, dates[1]);
+      await expect(
+        program.methods
+          .exerciseScheduled(dates[0])
+          .accounts({
+            mintBase: wsol,
+            data: pda,
+            buyer: buyer.publicKey,
+            tokenProgram: token.TOKEN_PROGRAM_ID,
+          })
+          .signers([buyer])
+          .rpc()
+      ).rejects.toThrowError(
+        /AnchorError thrown in programs\/solana-options\/src\/instructions\/exercise_scheduled.rs:\d+. Error Code: InvalidExerciseDate. Error Number: 6017. Error Message: Exercise date is not scheduled or has been superseded./
+      );
+    });
+
+    it("Can reject dates after expiry", async () => {
+      await expect(fixtureBermudan([1300])).rejects.toThrowError(
+        /AnchorError thrown in programs\/solana-options\/src\/instructions\/initialize.rs:\d+. Error Code: InvalidExerciseDate. Error Number: 6017. Error Message: Exercise date is not scheduled or has been superseded./
+      );
+    });
+
+    it("Can reject dates closer than the mark grace period", async () => {
+      await expect(fixtureBermudan([30, 60])).rejects.toThrowError(
+        /AnchorError thrown in programs\/solana-options\/src\/instructions\/initialize.rs:\d+. Error Code: InvalidExerciseDate. Error Number: 6017. Error Message: Exercise date is not scheduled or has been superseded./
+      );
+    });
+  });
+
+  describe("Averaged settlement", () => {
+    const markAt = async (
+      program: Program<SolanaOptions>,
+      setPrice: (price: number, time?: Date) => void,
+      expiry: anchor.BN,
+      price: number,
+      secondsBefore: number
+    ) => {
+      setPrice(price, new Date((expiry.toNumber() - secondsBefore) * 1000));
+      // Wait to avoid getting the error "This transaction has already been processed"
+      await new Promise((resolve) => setTimeout(resolve, 3));
+      await program.methods
+        .mark(expiry)
+        .accounts({ priceUpdate, market })
+        .rpc();
+    };
+
+    // One print per sample slot, the latest at expiry. The 30 minute window
+    // needs at least 15 to finalize
+    const markSamples = async (
+      program: Program<SolanaOptions>,
+      setPrice: (price: number, time?: Date) => void,
+      expiry: anchor.BN,
+      prices: number[]
+    ) => {
+      for (const [i, price] of prices.entries()) {
+        const secondsBefore = (prices.length - 1 - i) * 60;
+        await markAt(program, setPrice, expiry, price, secondsBefore);
+      }
+    };
+    const SAMPLES = [...Array(8).fill(3000), ...Array(8).fill(5000)];
+
+    it("Can finalize the average of spaced samples", async () => {
+      const { program, context, setPrice } = await fixtureDeployed();
+      const expiry = new anchor.BN(Math.floor(Date.now() / 1000) + 180);
+
+      await markSamples(program, setPrice, expiry, SAMPLES);
+
+      await warpTo(context, expiry.add(MARK_GRACE_PERIOD));
+      await program.methods.markFinalize(expiry, SOL_FEED_ID).rpc();
+
+      const expiryData = await program.account.expiryData.fetch(
+        getExpiryPda({
+          expiry: new Date(expiry.toNumber() * 1000),
+          feedId: SOL_FEED_ID,
+          programId: program.programId,
+        })
+      );
+      expect(expiryData.sampleCount).to.equal(16);
+      expect(expiryData.isFinalized).to.equal(true);
+      expect(expiryData.averagePrice).toBeBN(new BN(4000 * 10 ** 8));
+      expect(expiryData.price).toBeBN(new BN(5000 * 10 ** 8));
+    });
+
+    it("Can reject marks once finalized", async () => {
+      const { program, context, setPrice } = await fixtureDeployed();
+      const expiry = new anchor.BN(Math.floor(Date.now() / 1000) + 180);
+
+      await markSamples(program, setPrice, expiry, SAMPLES);
+      await warpTo(context, expiry.add(MARK_GRACE_PERIOD));
+      await program.methods.markFinalize(expiry, SOL_FEED_ID).rpc();
+
+      await expect(
+        markAt(program, setPrice, expiry, 5000, 0)
+      ).rejects.toThrowError(
+        /AnchorError thrown in programs\/solana-options\/src\/instructions\/mark.rs:\d\d. Error Code: MarkFinalized. Error Number: 6018. Error Message: Expiry mark is already finalized./
+      );
+    });
+
+    it("Can reject finalize before the grace period is over", async () => {
+      const { program, context, setPrice } = await fixtureDeployed();
+      const expiry = new anchor.BN(Math.floor(Date.now() / 1000) + 180);
+
+      await markAt(program, setPrice, expiry, 3000, 120);
+      await warpTo(context, expiry);
+
+      await expect(
+        program.methods.markFinalize(expiry, SOL_FEED_ID).rpc()
+      ).rejects.toThrowError(
+        /AnchorError thrown in programs\/solana-options\/src\/instructions\/mark_finalize.rs:\d\d. Error Code: MarkNotFinal. Error Number: 6025. Error Message: Expiry mark can still change./
+      );
+    });
+
+    it("Can reject finalize without enough samples", async () => {
+      const { program, context, setPrice } = await fixtureDeployed();
+      const expiry = new anchor.BN(Math.floor(Date.now() / 1000) + 180);
+
+      await markSamples(program, setPrice, expiry, SAMPLES.slice(2));
+      await warpTo(context, expiry.add(MARK_GRACE_PERIOD));
+
+      await expect(
+        program.methods.markFinalize(expiry, SOL_FEED_ID).rpc()
+      ).rejects.toThrowError(
+        /AnchorError thrown in programs\/solana-options\/src\/instructions\/mark_finalize.rs:\d\d. Error Code: NotEnoughSamples. Error Number: 6027. Error Message: Not enough samples to average the mark./
+      );
+    });
+
+    it("Can reject later prints after the grace period", async () => {
+      const { program, context, setPrice } = await fixtureDeployed();
+      const expiry = new anchor.BN(Math.floor(Date.now() / 1000) + 180);
+
+      await markAt(program, setPrice, expiry, 3000, 30);
+      await warpTo(context, expiry.add(MARK_GRACE_PERIOD));
+
+      // Same sample slot as the first print, so it can only update the spot
+      await expect(
+        markAt(program, setPrice, expiry, 5000, 10)
+      ).rejects.toThrowError(
+        /AnchorError thrown in programs\/solana-options\/src\/instructions\/mark.rs:\d+. Error Code: PriceIrrelevant. Error Number: 6007. Error Message: Price not close to expiry./
+      );
+    });
+
+    it("Can backfill samples without moving the spot price", async () => {
+      const { program, context, setPrice } = await fixtureDeployed();
+      const expiry = new anchor.BN(Math.floor(Date.now() / 1000) + 180);
+
+      await markAt(program, setPrice, expiry, 3000, 0);
+      await warpTo(context, expiry.add(MARK_GRACE_PERIOD));
+      await markAt(program, setPrice, expiry, 5000, 120);
+
+      const expiryData = await program.account.expiryData.fetch(
+        getExpiryPda({
+          expiry: new Date(expiry.toNumber() * 1000),
+          feedId: SOL_FEED_ID,
+          programId: program.programId,
+        })
+      );
+      expect(expiryData.sampleCount).to.equal(2);
+      expect(expiryData.sampleBuckets).toBeBN(new BN(0b101));
+      expect(expiryData.priceSum).toBeBN(new BN(8000 * 10 ** 8));
+      expect(expiryData.price).toBeBN(new BN(3000 * 10 ** 8));
+      expect(expiryData.publishTime).toBeBN(expiry);
+    });
+
+    it("Can reject averaging for early exercise styles", async () => {
+      const { program, wsol, usdc, buyer } = await fixtureDeployed();
+      const expiry = new anchor.BN(Math.floor(Date.now() / 1000) + 180);
+
+      await expect(
+        program.methods
+          .initialize(
+            new anchor.BN("1000"),
+            new anchor.BN("3500"),
+            expiry,
+            new anchor.BN(10),
+            new anchor.BN(6),
+            { cash: {} },
+            { american: {} },
+            [],
+            { average: {} }
+          )
+          .accounts({
+            mintBase: wsol,
+            mintPremium: wsol,
+            mintQuote: usdc,
+            buyer: buyer.publicKey,
+            tokenProgram: token.TOKEN_PROGRAM_ID,
+            tokenProgramPremium: token.TOKEN_PROGRAM_ID,
+          })
+          .rpc()
+      ).rejects.toThrowError(
+        /AnchorError thrown in programs\/solana-options\/src\/instructions\/initialize.rs:\d+. Error Code: InvalidPriceKind. Error Number: 6029. Error Message: Averaged prices are only supported for European options./
+      );
+    });
+
+    it("Can exercise on the finalized average", async () => {
+      const fixture = await fixtureDeployed();
+      const { program, context, wsol, usdc, buyer, seller, setPrice } =
+        fixture;
+      const expiry = new anchor.BN(Math.floor(Date.now() / 1000) + 180);
+      await program.methods
+        .initialize(
+          new anchor.BN("1000"),
+          new anchor.BN("3500"),
+          expiry,
+          new anchor.BN(10),
+          new anchor.BN(6),
+          { cash: {} },
+          { european: {} },
+          [],
+          { average: {} }
+        )
+        .accounts({
+          mintBase: wsol,
+          mintPremium: wsol,
+          mintQuote: usdc,
+          buyer: buyer.publicKey,
+          tokenProgram: token.TOKEN_PROGRAM_ID,
+          tokenProgramPremium: token.TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+      const pda = getPda({
+        nonce: 6n,
+        programId: program.programId,
+        seller: seller.publicKey,
+      });
+
+      await program.methods
+        .buy(new anchor.BN(10))
+        .accounts({
+          data: pda,
+          seller: seller.publicKey,
+          buyer: buyer.publicKey,
+          mintPremium: wsol,
+          payer: buyer.publicKey,
+          tokenProgram: token.TOKEN_PROGRAM_ID,
+        })
+        .signers([buyer])
+        .rpc();
+
+      await markSamples(program, setPrice, expiry, SAMPLES);
+      await warpTo(context, expiry);
+
+      const exercise = () =>
+        program.methods
+          .exercise()
+          .accounts({
+            mintBase: wsol,
+            mintQuote: usdc,
+            data: pda,
+            buyer: buyer.publicKey,
+            tokenProgram: token.TOKEN_PROGRAM_ID,
+          })
+          .signers([buyer])
+          .rpc();
+
+      await expect(exercise()).rejects.toThrowError(
+        /AnchorError thrown in programs\/solana-options\/src\/instructions\/exercise.rs:\d+. Error Code: OptionNotMarked. Error Number: 6008. Error Message: Option not marked./
+      );
+
+      await warpTo(context, expiry.add(MARK_GRACE_PERIOD));
+      await program.methods.markFinalize(expiry, SOL_FEED_ID).rpc();
+      await exercise();
+
+      // Average of 4000 against the 3500 strike, not the last print of 5000
+      expect(
+        await getAtaTokenBalance(context.banksClient, wsol, buyer.publicKey)
+      ).to.equal(BigInt(990 + 125));
+    });
+  });
+
+  describe("Auto exercise", () => {
+    it("Can let anyone exercise for the buyer and take a bounty", async () => {
+      const { program, pda, buyer, wsol, context, setPrice, expiry, market } =
+        await fixtureBought();
+
+      setPrice(4000);
+      await program.methods
+        .mark(expiry)
+        .accounts({ priceUpdate, market })
+        .rpc();
+
+      const keeper = Keypair.generate();
+      await airdrop(context, keeper.publicKey, 1 * LAMPORTS_PER_SOL);
+
+      await warpTo(context, expiry.add(MARK_GRACE_PERIOD));
+      await program.methods
+        .autoExercise()
+        .accounts({
+          caller: keeper.publicKey,
+          buyer: buyer.publicKey,
+          mintBase: wsol,
+          data: pda,
+          tokenProgram: token.TOKEN_PROGRAM_ID,
+        })
+        .signers([keeper])
+        .rpc();
+
+      // 1% of the 125 in the money
+      expect(
+        await getAtaTokenBalance(context.banksClient, wsol, buyer.publicKey)
+      ).to.equal(BigInt(990 + 124));
+      expect(
+        await getAtaTokenBalance(context.banksClient, wsol, keeper.publicKey)
+      ).to.equal(BigInt(1));
+      expect(
+        (await program.account.coveredCall.fetch(pda)).isExercised
+      ).to.equal(true);
+    });
+
+    it("Can let anyone exercise a put for the buyer", async () => {
+      const { program, pda, buyer, usdc, context, setPrice, expiry, market } =
+        await fixturePutBought();
+
+      setPrice(3000);
+      await program.methods
+        .mark(expiry)
+        .accounts({ priceUpdate, market })
+        .rpc();
+
+      const keeper = Keypair.generate();
+      await airdrop(context, keeper.publicKey, 1 * LAMPORTS_PER_SOL);
+
+      await warpTo(context, expiry.add(MARK_GRACE_PERIOD));
+      await program.methods
+        .autoExercisePut()
+        .accounts({
+          caller: keeper.publicKey,
+          buyer: buyer.publicKey,
+          mintQuote: usdc,
+          data: pda,
+          tokenProgram: token.TOKEN_PROGRAM_ID,
+        })
+        .signers([keeper])
+        .rpc();
+
+      // 1% of the 500 in the money
+      expect(
+        await getAtaTokenBalance(context.banksClient, usdc, buyer.publicKey)
+      ).to.equal(BigInt(990 + 495));
+      expect(
+        await getAtaTokenBalance(context.banksClient, usdc, keeper.publicKey)
+      ).to.equal(BigInt(5));
+      expect(
+        (await program.account.cashSecuredPut.fetch(pda)).isExercised
+      ).to.equal(true);
+    });
+
+    it("Can reject exercise before the mark is final", async () => {
+      const {
+        program,
+        pda,
+        buyer,
+        wsol,
+        usdc,
+        context,
+        setPrice,
+        expiry,
+        market,
+      } = await fixtureBought();
+
+      setPrice(4000);
+      await program.methods
+        .mark(expiry)
+        .accounts({ priceUpdate, market })
+        .rpc();
+
+      await warpTo(context, expiry.add(new anchor.BN(100)));
+      await expect(
+        program.methods
+          .autoExercise()
+          .accounts({
+            buyer: buyer.publicKey,
+            mintBase: wsol,
+            data: pda,
+            tokenProgram: token.TOKEN_PROGRAM_ID,
+          })
+          .rpc()
+      ).rejects.toThrowError(
+        /AnchorError thrown in programs\/solana-options\/src\/instructions\/auto_exercise.rs:\d+. Error Code: MarkNotFinal. Error Number: 6025. Error Message: Expiry mark can still change./
+      );
+
+      // The buyer waits for the same final mark as the seller
+      await expect(
+        program.methods
+          .exercise()
+          .accounts({
+            mintBase: wsol,
+            mintQuote: usdc,
+            data: pda,
+            buyer: buyer.publicKey,
+            tokenProgram: token.TOKEN_PROGRAM_ID,
+          })
+          .signers([buyer])
+          .rpc()
+      ).rejects.toThrowError(
+        /AnchorError thrown in programs\/solana-options\/src\/instructions\/exercise.rs:\d+. Error Code: MarkNotFinal. Error Number: 6025. Error Message: Expiry mark can still change./
+      );
+
+      await warpTo(context, expiry.add(MARK_GRACE_PERIOD));
+      await program.methods
+        .autoExercise()
+        .accounts({
+          caller: buyer.publicKey,
+          buyer: buyer.publicKey,
+          mintBase: wsol,
+          data: pda,
+          tokenProgram: token.TOKEN_PROGRAM_ID,
+        })
+        .signers([buyer])
+        .rpc();
+      expect(
+        (await program.account.coveredCall.fetch(pda)).isExercised
+      ).to.equal(true);
+    });
+
+    it("Can reject if out of the money", async () => {
+      const { program, pda, buyer, wsol, context, setPrice, expiry, market } =
+        await fixtureBought();
+
+      setPrice(3000);
+      await program.methods
+        .mark(expiry)
+        .accounts({ priceUpdate, market })
+        .rpc();
+
+      await warpTo(context, expiry.add(MARK_GRACE_PERIOD));
+      await expect(
+        program.methods
+          .autoExercise()
+          .accounts({
+            buyer: buyer.publicKey,
+            mintBase: wsol,
+            data: pda,
+            tokenProgram: token.TOKEN_PROGRAM_ID,
+          })
+          .rpc()
+      ).rejects.toThrowError(
+        /AnchorError thrown in programs\/solana-options\/src\/instructions\/auto_exercise.rs:\d+. Error Code: OptionOutOfTheMoney. Error Number: 6016. Error Message: Option is out of the money./
+      );
+    });
+  });
+
+  describe("Config", () => {
+    const params = {
+      markWindow: new BN(30 * 60),
+      maxConfBps: 100,
+      feePremiumBps: 0,
+      feeSettlementBps: 0,
+      bountyBps: 100,
+      allowedMints: [],
+    };
+
+    const initialize = (
+      program: Program<SolanaOptions>,
+      wsol: PublicKey,
+      usdc: PublicKey
+    ) =>
+      program.methods
+        .initialize(
+          new anchor.BN("1000"),
+          new anchor.BN("3500"),
+          new anchor.BN(Math.floor(Date.now() / 1000) + 180),
+          new anchor.BN(10),
+          new anchor.BN(7),
+          { cash: {} },
+          { european: {} },
+          [],
+          { spot: {} }
+        )
+        .accounts({
+          mintBase: wsol,
+          mintPremium: wsol,
+          mintQuote: usdc,
+          buyer: null,
+          tokenProgram: token.TOKEN_PROGRAM_ID,
+          tokenProgramPremium: token.TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+    it("Can reject initialize if not upgrade authority", async () => {
+      const { program, buyer, programData } = await fixtureStarted();
+
+      await expect(
+        program.methods
+          .initializeConfig(params)
+          .accounts({ admin: buyer.publicKey, programData })
+          .signers([buyer])
+          .rpc()
+      ).rejects.toThrowError(
+        "AnchorError caused by account: program_data. Error Code: ConstraintRaw. Error Number: 2003. Error Message: A raw constraint was violated."
+      );
+    });
+
+    it("Can pause everything as admin", async () => {
+      const { program, wsol, usdc } = await fixtureDeployed();
+
+      await program.methods.setPaused(0xffffffff).rpc();
+
+      await expect(initialize(program, wsol, usdc)).rejects.toThrowError(
+        /AnchorError thrown in programs\/solana-options\/src\/instructions\/initialize.rs:\d\d. Error Code: ProtocolPaused. Error Number: 6021. Error Message: Instruction is paused./
+      );
+
+      await program.methods.setPaused(0).rpc();
+      await initialize(program, wsol, usdc);
+    });
+
+    it("Can pause a single instruction", async () => {
+      const { program, pda, seller, buyer, wsol, setPrice, expiry, market } =
+        await fixtureInitialized();
+
+      // PAUSE_BUY
+      await program.methods.setPaused(2).rpc();
+
+      await expect(
+        program.methods
+          .buy(new anchor.BN(10))
+          .accounts({
+            data: pda,
+            seller: seller.publicKey,
+            buyer: buyer.publicKey,
+            mintPremium: wsol,
+            payer: buyer.publicKey,
+            tokenProgram: token.TOKEN_PROGRAM_ID,
+          })
+          .signers([buyer])
+          .rpc()
+      ).rejects.toThrowError(/Error Code: ProtocolPaused. Error Number: 6021/);
+
+      // Marking is still allowed
+      setPrice(4000);
+      await program.methods
+        .mark(expiry)
+        .accounts({ priceUpdate, market })
+        .rpc();
+    });
+
+    it("Can close unbought option while paused", async () => {
+      const { program, pda, seller, wsol, context } =
+        await fixtureInitialized();
+
+      await program.methods.setPaused(0xffffffff).rpc();
+
+      await program.methods
+        .close()
+        .accounts({
+          mintBase: wsol,
+          data: pda,
+          seller: seller.publicKey,
+          payer: seller.publicKey,
+          expiry: null,
+          tokenProgram: token.TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+      expect(
+        await getAtaTokenBalance(context.banksClient, wsol, seller.publicKey)
+      ).to.equal(BigInt(1000));
+    });
+
+    it("Can reject pause if not admin", async () => {
+      const { program, buyer } = await fixtureDeployed();
+
+      await expect(
+        program.methods
+          .setPaused(0xffffffff)
+          .accounts({ admin: buyer.publicKey })
+          .signers([buyer])
+          .rpc()
+      ).rejects.toThrowError(
+        "AnchorError caused by account: admin. Error Code: ConstraintRaw. Error Number: 2003. Error Message: A raw constraint was violated."
+      );
+    });
+
+    it("Can reject options on a pair without a market", async () => {
+      const { program, wsol, usdc } = await fixtureDeployed();
+
+      await expect(initialize(program, usdc, wsol)).rejects.toThrowError(
+        "AnchorError caused by account: market. Error Code: AccountNotInitialized. Error Number: 3012. Error Message: The program expected this account to be already initialized."
+      );
+    });
+
+    it("Can reject expiry outside the market range", async () => {
+      const { program, wsol, usdc, market } = await fixtureDeployed();
+
+      await program.methods
+        .updateMarket({ ...marketParams, maxTimeToExpiry: new BN(60) })
+        .accounts({ market })
+        .rpc();
+
+      await expect(initialize(program, wsol, usdc)).rejects.toThrowError(
+        /Error Code: ExpiryOutOfRange. Error Number: 6022/
+      );
+    });
+
+    it("Can reject size below the market minimum", async () => {
+      const { program, wsol, usdc, market } = await fixtureDeployed();
+
+      await program.methods
+        .updateMarket({ ...marketParams, minAmountBase: new BN(1001) })
+        .accounts({ market })
+        .rpc();
+
+      await expect(initialize(program, wsol, usdc)).rejects.toThrowError(
+        /Error Code: AmountTooSmall. Error Number: 6023/
+      );
+    });
+
+    it("Can reject market update if not admin", async () => {
+      const { program, buyer, market } = await fixtureDeployed();
+
+      await expect(
+        program.methods
+          .updateMarket(marketParams)
+          .accounts({ admin: buyer.publicKey, market })
+          .signers([buyer])
+          .rpc()
+      ).rejects.toThrowError(
+        "AnchorError caused by account: admin. Error Code: ConstraintRaw. Error Number: 2003. Error Message: A raw constraint was violated."
+      );
+    });
+
+    it("Can reject out of range parameters", async () => {
+      const { program } = await fixtureDeployed();
+
+      await expect(
+        program.methods.updateConfig({ ...params, bountyBps: 10_001 }).rpc()
+      ).rejects.toThrowError(/Error Code: InvalidConfig. Error Number: 6019/);
+    });
+
+    it("Can tighten the accepted confidence interval", async () => {
+      const { program, setPrice, market } = await fixtureDeployed();
+
+      // Fixture confidence of 0.12 is about 9 bps of a price of 130
+      await program.methods.updateConfig({ ...params, maxConfBps: 5 }).rpc();
+
+      const expiry = new Date();
+      setPrice(130, new Date(expiry.getTime() - 1000));
+
+      await expect(
+        program.methods
+          .mark(new anchor.BN(Math.floor(expiry.getTime() / 1000)))
+          .accounts({ priceUpdate, market })
+          .rpc()
+      ).rejects.toThrowError(
+        /AnchorError thrown in programs\/solana-options\/src\/instructions\/mark.rs:\d\d. Error Code: ConfidenceTooWide. Error Number: 6014. Error Message: Oracle confidence interval is too wide./
+      );
+    });
+
+    it("Can reject update if not admin", async () => {
+      const { program, buyer } = await fixtureDeployed();
+
+      await expect(
+        program.methods
+          .updateConfig(params)
+          .accounts({ admin: buyer.publicKey })
+          .signers([buyer])
+          .rpc()
+      ).rejects.toThrowError(
+        "AnchorError caused by account: admin. Error Code: ConstraintRaw. Error Number: 2003. Error Message: A raw constraint was violated."
+      );
+    });
+
+    it("Can transfer admin", async () => {
+      const { program, buyer } = await fixtureDeployed();
+
+      await program.methods
+        .transferAdmin()
+        .accounts({ newAdmin: buyer.publicKey })
+        .rpc();
+
+      await program.methods
+        .updateConfig({ ...params, bountyBps: 50 })
+        .accounts({ admin: buyer.publicKey })
+        .signers([buyer])
+        .rpc();
+
+      const [config] = PublicKey.findProgramAddressSync(
+        [Buffer.from("config")],
+        program.programId
+      );
+      const data = await program.account.config.fetch(config);
+      expect(data.admin).toStrictEqual(buyer.publicKey);
+      expect(data.params.bountyBps).to.equal(50);
+    });
+  });
+
+  describe("Fees", () => {
+    const params = {
+      markWindow: new BN(30 * 60),
+      maxConfBps: 100,
+      feePremiumBps: 1000,
+      feeSettlementBps: 800,
+      bountyBps: 100,
+      allowedMints: [],
+    };
+
+    const fixtureFees = async () => {
+      const fixture = await fixtureInitialized();
+      const { program, pda, seller, buyer, wsol } = fixture;
+
+      await program.methods.updateConfig(params).rpc();
+
+      // Created along with the market
+      const feeVault = getFeeVaultPda({
+        mint: wsol,
+        programId: program.programId,
+      });
+      await program.methods
+        .buy(new anchor.BN(10))
+        .accounts({
+          data: pda,
+          seller: seller.publicKey,
+          buyer: buyer.publicKey,
+          mintPremium: wsol,
+          payer: buyer.publicKey,
+          feeVaultPremium: feeVault,
+          tokenProgram: token.TOKEN_PROGRAM_ID,
+        })
+        .signers([buyer])
+        .rpc();
+
+      const getFees = async () =>
+        (await getAccount(fixture.context.banksClient, feeVault)).amount;
+
+      return { ...fixture, feeVault, getFees };
+    };
+
+    it("Can take a fee on the premium", async () => {
+      const { wsol, seller, context, getFees } = await fixtureFees();
+
+      expect(
+        await getAtaTokenBalance(context.banksClient, wsol, seller.publicKey)
+      ).to.equal(BigInt(9));
+      expect(await getFees()).to.equal(BigInt(1));
+    });
+
+    it("Can take a premium fee in a mint no market lists", async () => {
+      const { program, context, wsol, usdc, seller, buyer } =
+        await fixtureDeployed();
+      await program.methods.updateConfig(params).rpc();
+
+      const bonk = await createMint(
+        context.banksClient,
+        context.payer,
+        authority.publicKey,
+        authority.publicKey,
+        5
+      );
+      await fundAtaAccount(context.banksClient, bonk, buyer, 100);
+
+      // The seller creates the premium fee vault along with the option
+      await program.methods
+        .initialize(
+          new anchor.BN("1000"),
+          new anchor.BN("3500"),
+          new anchor.BN(Math.floor(Date.now() / 1000) + 180),
+          new anchor.BN(10),
+          new anchor.BN(0),
+          { cash: {} },
+          { european: {} },
+          [],
+          { spot: {} }
+        )
+        .accounts({
+          mintBase: wsol,
+          mintPremium: bonk,
+          mintQuote: usdc,
+          buyer: buyer.publicKey,
+          tokenProgram: token.TOKEN_PROGRAM_ID,
+          tokenProgramPremium: token.TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+      const pda = getPda({
+        nonce: 0n,
+        programId: program.programId,
+        seller: seller.publicKey,
+      });
+      const feeVault = getFeeVaultPda({
+        mint: bonk,
+        programId: program.programId,
+      });
+      await program.methods
+        .buy(new anchor.BN(100))
+        .accounts({
+          data: pda,
+          seller: seller.publicKey,
+          buyer: buyer.publicKey,
+          mintPremium: bonk,
+          payer: buyer.publicKey,
+          feeVaultPremium: feeVault,
+          tokenProgram: token.TOKEN_PROGRAM_ID,
+        })
+        .signers([buyer])
+        .rpc();
+
+      // 10% of the premium
+      expect((await getAccount(context.banksClient, feeVault)).amount).to.equal(
+        BigInt(10)
+      );
+      expect(
+        await getAtaTokenBalance(context.banksClient, bonk, seller.publicKey)
+      ).to.equal(BigInt(90));
+    });
+
+    it("Can take a fee on the settlement", async () => {
+      const fixture = await fixtureFees();
+      const {
+        program,
+        pda,
+        buyer,
+        wsol,
+        usdc,
+        context,
+        setPrice,
+        expiry,
+        market,
+      } = fixture;
+
+      setPrice(4000);
+      await program.methods
+        .mark(expiry)
+        .accounts({ priceUpdate, market })
+        .rpc();
+
+      await warpTo(context, expiry.add(MARK_GRACE_PERIOD));
+      await program.methods
+        .exercise()
+        .accounts({
+          mintBase: wsol,
+          mintQuote: usdc,
+          data: pda,
+          buyer: buyer.publicKey,
+          feeVaultBase: fixture.feeVault,
+          tokenProgram: token.TOKEN_PROGRAM_ID,
+        })
+        .signers([buyer])
+        .rpc();
+
+      // 8% of the 125 in the money
+      expect(
+        await getAtaTokenBalance(context.banksClient, wsol, buyer.publicKey)
+      ).to.equal(BigInt(990 + 115));
+      expect(await fixture.getFees()).to.equal(BigInt(1 + 10));
+    });
+
+    it("Can emit the settlement fee in the exercise event", async () => {
+      const fixture = await fixtureFees();
+      const {
+        program,
+        pda,
+        buyer,
+        wsol,
+        usdc,
+        context,
+        setPrice,
+        expiry,
+        market,
+      } = fixture;
+
+      setPrice(4000);
+      await program.methods
+        .mark(expiry)
+        .accounts({ priceUpdate, market })
+        .rpc();
+
+      await warpTo(context, expiry.add(MARK_GRACE_PERIOD));
+      const tx = await program.methods
+        .exercise()
+        .accounts({
+          mintBase: wsol,
+          mintQuote: usdc,
+          data: pda,
+          buyer: buyer.publicKey,
+          feeVaultBase: fixture.feeVault,
+          tokenProgram: token.TOKEN_PROGRAM_ID,
+        })
+        .transaction();
+      tx.recentBlockhash = context.lastBlockhash;
+      tx.feePayer = buyer.publicKey;
+      tx.sign(buyer);
+      const meta = await context.banksClient.processTransaction(tx);
+
+      const data = meta.innerInstructions
+        .flat()
+        .map(({ instruction }) => Buffer.from(instruction.data))
+        .find((data) => data.subarray(0, 8).equals(EVENT_IX_TAG_LE));
+      expect(data).toBeDefined();
+      const event = program.coder.events.decode(
+        data!.subarray(8).toString("base64")
+      );
+      expect(event?.name).to.equal("OptionExercised");
+      expect(event?.data.option.toBase58()).to.equal(pda.toBase58());
+      expect(event?.data.buyer.toBase58()).to.equal(buyer.publicKey.toBase58());
+      // 8% of the 125 in the money
+      expect(event?.data.amountSeller.toString()).to.equal("875");
+      expect(event?.data.amountBuyer.toString()).to.equal("115");
+      expect(event?.data.fee.toString()).to.equal("10");
+    });
+
+    it("Can reject a fee without the fee vault", async () => {
+      const fixture = await fixtureFees();
+      const { program, pda, buyer, wsol, usdc, context, setPrice, expiry } =
+        fixture;
+
+      setPrice(4000);
+      await program.methods
+        .mark(expiry)
+        .accounts({ priceUpdate, market: fixture.market })
+        .rpc();
+
+      await warpTo(context, expiry.add(MARK_GRACE_PERIOD));
+      await expect(
+        program.methods
+          .exercise()
+          .accounts({
+            mintBase: wsol,
+            mintQuote: usdc,
+            data: pda,
+            buyer: buyer.publicKey,
+            feeVaultBase: null,
+            tokenProgram: token.TOKEN_PROGRAM_ID,
+          })
+          .signers([buyer])
+          .rpc()
+      ).rejects.toThrowError(
+        /Error Code: FeeVaultRequired. Error Number: 6028. Error Message: Fee vault is required to charge a fee./
+      );
+    });
+
+    it("Can withdraw fees as admin", async () => {
+      const { program, wsol, seller, context, getFees } = await fixtureFees();
+
+      await program.methods
+        .withdrawFees(new anchor.BN(1))
+        .accounts({
+          mint: wsol,
+          destination: token.getAssociatedTokenAddressSync(
+            wsol,
+            seller.publicKey
+          ),
+          tokenProgram: token.TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+      expect(
+        await getAtaTokenBalance(context.banksClient, wsol, seller.publicKey)
+      ).to.equal(BigInt(9 + 1));
+      expect(await getFees()).to.equal(BigInt(0));
+    });
+
+    it("Can reject withdraw if not admin", async () => {
+      const { program, wsol, buyer } = await fixtureFees();
+
+      await expect(
+        program.methods
+          .withdrawFees(new anchor.BN(1))
+          .accounts({
+            admin: buyer.publicKey,
+            mint: wsol,
+            destination: token.getAssociatedTokenAddressSync(
+              wsol,
+              buyer.publicKey
+            ),
+            tokenProgram: token.TOKEN_PROGRAM_ID,
+          })
+          .signers([buyer])
+          .rpc()
+      ).rejects.toThrowError(
+        "AnchorError caused by account: admin. Error Code: ConstraintRaw. Error Number: 2003. Error Message: A raw constraint was violated."
+      );
+    });
+  });
+
+  describe("Token-2022", () => {
+    const initialize = (program: Program<SolanaOptions>, mint: PublicKey) =>
+      program.methods
+        .initialize(
+          new anchor.BN("1000"),
+          new anchor.BN("3500"),
+          new anchor.BN(Math.floor(Date.now() / 1000) + 180),
+          new anchor.BN(10),
+          new anchor.BN(0),
+          { cash: {} },
+          { european: {} },
+          [],
+          { spot: {} }
+        )
+        .accounts({
+          mintBase: mint,
+          mintPremium: mint,
+          mintQuote: mint,
+          buyer: null,
+          tokenProgram: token.TOKEN_2022_PROGRAM_ID,
+          tokenProgramPremium: token.TOKEN_2022_PROGRAM_ID,
+        })
+        .rpc();
+
+    it("Can fully collateralize with a transfer fee mint", async () => {
+      const { context, program, provider, seller } = await fixtureDeployed();
+
+      // 1% fee, so depositing 1000 takes 1011 and withdrawing 1000 pays 990
+      const mint = await createMint2022(
+        context,
+        provider,
+        [token.ExtensionType.TransferFeeConfig],
+        (mint) => [
+          token.createInitializeTransferFeeConfigInstruction(
+            mint,
+            authority.publicKey,
+            authority.publicKey,
+            100,
+            BigInt(1000),
+            token.TOKEN_2022_PROGRAM_ID
+          ),
+        ]
+      );
+      await fundAtaAccount2022(provider, mint, seller.publicKey, 2000);
+      await program.methods
+        .initializeMarket(SOL_FEED_ID, marketParams)
+        .accounts({
+          mintBase: mint,
+          mintQuote: mint,
+          tokenProgram: token.TOKEN_2022_PROGRAM_ID,
+          tokenProgramQuote: token.TOKEN_2022_PROGRAM_ID,
+        })
+        .rpc();
+
+      await initialize(program, mint);
+
+      const pda = getPda({
+        nonce: 0n,
+        programId: program.programId,
+        seller: seller.publicKey,
+      });
+      expect(
+        await getAtaTokenBalance2022(context.banksClient, mint, pda)
+      ).to.equal(BigInt(1000));
+      expect(
+        await getAtaTokenBalance2022(
+          context.banksClient,
+          mint,
+          seller.publicKey
+        )
+      ).to.equal(BigInt(2000 - 1011));
+
+      // Withheld fees are harvested so the vault can be closed
+      await program.methods
+        .close()
+        .accounts({
+          mintBase: mint,
+          data: pda,
+          seller: seller.publicKey,
+          tokenProgram: token.TOKEN_2022_PROGRAM_ID,
+        })
+        .rpc();
+
+      expect(
+        await context.banksClient.getAccount(
+          token.getAssociatedTokenAddressSync(
+            mint,
+            pda,
+            true,
+            token.TOKEN_2022_PROGRAM_ID
+          )
+        )
+      ).to.equal(null);
+      expect(
+        await getAtaTokenBalance2022(
+          context.banksClient,
+          mint,
+          seller.publicKey
+        )
+      ).to.equal(BigInt(2000 - 1011 + 990));
+    });
+
+    it("Can reject a non-transferable mint", async () => {
+      const { context, program, provider } = await fixtureDeployed();
+
+      const mint = await createMint2022(
+        context,
+        provider,
+        [token.ExtensionType.NonTransferable],
+        (mint) => [
+          token.createInitializeNonTransferableMintInstruction(
+            mint,
+            token.TOKEN_2022_PROGRAM_ID
+          ),
+        ]
+      );
+
+      await expect(
+        program.methods
+          .initializeMarket(SOL_FEED_ID, marketParams)
+          .accounts({
+            mintBase: mint,
+            mintQuote: mint,
+            tokenProgram: token.TOKEN_2022_PROGRAM_ID,
+            tokenProgramQuote: token.TOKEN_2022_PROGRAM_ID,
+          })
+          .rpc()
+      ).rejects.toThrowError(
+        /Error Code: MintExtensionNotAllowed. Error Number: 6024/
+      );
+    });
+
+    it("Can reject a permanent delegate mint unless allowed", async () => {
+      const { context, program, provider } = await fixtureDeployed();
+
+      const mint = await createMint2022(
+        context,
+        provider,
+        [token.ExtensionType.PermanentDelegate],
+        (mint) => [
+          token.createInitializePermanentDelegateInstruction(
+            mint,
+            authority.publicKey,
+            token.TOKEN_2022_PROGRAM_ID
+          ),
+        ]
+      );
+      const initializeMarket = () =>
+        program.methods
+          .initializeMarket(SOL_FEED_ID, marketParams)
+          .accounts({
+            mintBase: mint,
+            mintQuote: mint,
+            tokenProgram: token.TOKEN_2022_PROGRAM_ID,
+            tokenProgramQuote: token.TOKEN_2022_PROGRAM_ID,
+          })
+          .rpc();
+
+      await expect(initializeMarket()).rejects.toThrowError(
+        /Error Code: MintExtensionNotAllowed. Error Number: 6024/
+      );
+
+      await program.methods
+        .updateConfig({
+          markWindow: new BN(30 * 60),
+          maxConfBps: 100,
+          feePremiumBps: 0,
+          feeSettlementBps: 0,
+          bountyBps: 100,
+          allowedMints: [mint],
+        })
+        .rpc();
+      await initializeMarket();
+    });
+  });
+
+  describe("Physical settlement", () => {
+    it("Can exercise physically settled option", async () => {
+      const { program, pda, buyer, wsol, usdc, context, expiry } =
+        await fixturePhysicalBought();
+
+      await warpTo(context, expiry.add(new anchor.BN(100)));
+      await program.methods
+        .exercisePhysical()
+        .accounts({
+          mintBase: wsol,
+          mintQuote: usdc,
+          data: pda,
+          buyer: buyer.publicKey,
+          tokenProgram: token.TOKEN_PROGRAM_ID,
+          tokenProgramQuote: token.TOKEN_PROGRAM_ID,
+        })
+        .signers([buyer])
+        .rpc();
+
+      expect(
+        await getAtaTokenBalance(context.banksClient, wsol, buyer.publicKey)
+      ).to.equal(BigInt(990 + 1000));
+      expect(
+        await getAtaTokenBalance(context.banksClient, usdc, buyer.publicKey)
+      ).to.equal(BigInt(0));
+      expect(await getAtaTokenBalance(context.banksClient, wsol, pda)).to.equal(
+        BigInt(0)
+      );
+      expect(await getAtaTokenBalance(context.banksClient, usdc, pda)).to.equal(
+        BigInt(3500)
+      );
+    });
+
+    it("Can reject physical exercise after the exercise window", async () => {
+      const { program, pda, buyer, wsol, usdc, context, expiry } =
+        await fixturePhysicalBought();
+
+      await warpTo(context, expiry.add(MARK_GRACE_PERIOD));
+      await expect(
+        program.methods
+          .exercisePhysical()
+          .accounts({
+            mintBase: wsol,
+            mintQuote: usdc,
+            data: pda,
+            buyer: buyer.publicKey,
+            tokenProgram: token.TOKEN_PROGRAM_ID,
+            tokenProgramQuote: token.TOKEN_PROGRAM_ID,
+          })
+          .signers([buyer])
+          .rpc()
+      ).rejects.toThrowError(
+        /AnchorError thrown in programs\/solana-options\/src\/instructions\/exercise_physical.rs:\d\d. Error Code: OptionExpired. Error Number: 6001. Error Message: Option has expired./
+      );
+    });
+
+    it("Can close unexercised physical option without a mark", async () => {
+      const { program, pda, wsol, context, expiry, seller } =
+        await fixturePhysicalBought();
+
+      // The buyer still has the exercise window
+      await warpTo(context, expiry.add(new anchor.BN(100)));
+      const close = () =>
+        program.methods
+          .close()
+          .accounts({
+            mintBase: wsol,
+            data: pda,
+            seller: seller.publicKey,
+            payer: seller.publicKey,
+            expiry: null,
+            tokenProgram: token.TOKEN_PROGRAM_ID,
+          })
+          .signers([seller])
+          .rpc();
+      await expect(close()).rejects.toThrowError(
+        /AnchorError thrown in programs\/solana-options\/src\/instructions\/close.rs:\d+. Error Code: OptionCannotBeClosedYet. Error Number: 6004. Error Message: Option cannot be closed Yet./
+      );
+
+      await warpTo(context, expiry.add(MARK_GRACE_PERIOD));
+      await close();
+
+      expect(await context.banksClient.getAccount(pda)).to.equal(null);
+      expect(await getAtaTokenBalance(context.banksClient, wsol, pda)).to.equal(
+        BigInt(0)
+      );
+    });
+
+    it("Can close physically exercised option and collect quote", async () => {
+      const { program, pda, buyer, wsol, usdc, context, expiry, seller } =
+        await fixturePhysicalBought();
+
+      await warpTo(context, expiry.add(new anchor.BN(100)));
+      await program.methods
+        .exercisePhysical()
+        .accounts({
+          mintBase: wsol,
+          mintQuote: usdc,
+          data: pda,
+          buyer: buyer.publicKey,
+          tokenProgram: token.TOKEN_PROGRAM_ID,
+          tokenProgramQuote: token.TOKEN_PROGRAM_ID,
+        })
+        .signers([buyer])
+        .rpc();
+
+      await program.methods
+        .close()
+        .accounts({
+          mintBase: wsol,
+          mintQuote: usdc,
+          data: pda,
+          seller: seller.publicKey,
+          payer: seller.publicKey,
+          expiry: null,
+          tokenProgram: token.TOKEN_PROGRAM_ID,
+          tokenProgramQuote: token.TOKEN_PROGRAM_ID,
+        })
+        .signers([seller])
+        .rpc();
+
+      expect(await context.banksClient.getAccount(pda)).to.equal(null);
+      expect(
+        await context.banksClient.getAccount(
+          token.getAssociatedTokenAddressSync(usdc, pda, true)
+        )
+      ).to.equal(null);
+      expect(
+        await getAtaTokenBalance(context.banksClient, usdc, seller.publicKey)
+      ).to.equal(BigInt(3500));
+      expect(
+        await getAtaTokenBalance(context.banksClient, wsol, seller.publicKey)
+      ).to.equal(BigInt(10));
+    });
+
+    it("Can reject physical exercise of cash settled option", async () => {
+      const { program, pda, buyer, wsol, usdc, context, expiry } =
+        await fixtureBought();
+
+      await fundAtaAccount(context.banksClient, usdc, buyer, BigInt(3500));
+      await warpTo(context, expiry.add(new anchor.BN(100)));
+
+      await expect(
+        program.methods
+          .exercisePhysical()
+          .accounts({
+            mintBase: wsol,
+            mintQuote: usdc,
+            data: pda,
+            buyer: buyer.publicKey,
+            tokenProgram: token.TOKEN_PROGRAM_ID,
+            tokenProgramQuote: token.TOKEN_PROGRAM_ID,
+          })
+          .signers([buyer])
+          .rpc()
+      ).rejects.toThrowError(
+        /AnchorError thrown in programs\/solana-options\/src\/instructions\/exercise_physical.rs:\d+. Error Code: InvalidSettlementMode. Error Number: 6010. Error Message: Option uses a different settlement mode./
+      );
+    });
+  });
+
+  describe("Transfer buyer", () => {
+    it("Can transfer long side and let new buyer exercise", async () => {
+      const {
+        program,
+        pda,
+        buyer,
+        wsol,
+        usdc,
+        context,
+        expiry,
+        setPrice,
+        market,
+      } = await fixtureBought();
+
+      const newBuyer = Keypair.generate();
+      await airdrop(context, newBuyer.publicKey, 1 * LAMPORTS_PER_SOL);
+
+      await program.methods
+        .transferBuyer()
+        .accounts({
+          buyer: buyer.publicKey,
+          newBuyer: newBuyer.publicKey,
+          data: pda,
+        })
+        .signers([buyer])
+        .rpc();
+
+      expect(
+        (await program.account.coveredCall.fetch(pda)).buyer
+      ).toStrictEqual(newBuyer.publicKey);
+
+      setPrice(4000);
+      await program.methods
+        .mark(expiry)
+        .accounts({ priceUpdate, market })
+        .rpc();
+      await warpTo(context, expiry.add(MARK_GRACE_PERIOD));
+
+      await expect(
+        program.methods
+          .exercise()
+          .accounts({
+            mintBase: wsol,
+            mintQuote: usdc,
+            data: pda,
+            buyer: buyer.publicKey,
+            tokenProgram: token.TOKEN_PROGRAM_ID,
+          })
+          .signers([buyer])
+          .rpc()
+      ).rejects.toThrowError(
+        "AnchorError caused by account: buyer. Error Code: ConstraintRaw. Error Number: 2003. Error Message: A raw constraint was violated."
+      );
+
+      // The new buyer has no base account, exercise creates it
+      await program.methods
+        .exercise()
+        .accounts({
+          mintBase: wsol,
+          mintQuote: usdc,
+          data: pda,
+          buyer: newBuyer.publicKey,
+          tokenProgram: token.TOKEN_PROGRAM_ID,
+        })
+        .signers([newBuyer])
+        .rpc();
+
+      expect(
+        await getAtaTokenBalance(context.banksClient, wsol, newBuyer.publicKey)
+      ).to.equal(BigInt(125));
+    });
+
+    it("Can reject transfer by anyone but the buyer", async () => {
+      const { program, pda, seller } = await fixtureBought();
+
+      await expect(
+        program.methods
+          .transferBuyer()
+          .accounts({
+            buyer: seller.publicKey,
+            newBuyer: seller.publicKey,
+            data: pda,
+          })
+          .signers([seller])
+          .rpc()
+      ).rejects.toThrowError(
+        "AnchorError caused by account: buyer. Error Code: ConstraintRaw. Error Number: 2003. Error Message: A raw constraint was violated."
+      );
+    });
+
+    it("Can reject transfer of unbought option", async () => {
+      const { program, pda, buyer, seller } = await fixtureInitialized();
+
+      await expect(
+        program.methods
+          .transferBuyer()
+          .accounts({
+            buyer: buyer.publicKey,
+            newBuyer: seller.publicKey,
+            data: pda,
+          })
+          .signers([buyer])
+          .rpc()
+      ).rejects.toThrowError(
+        /AnchorError thrown in programs\/solana-options\/src\/instructions\/transfer_buyer.rs:\d\d. Error Code: OptionNotPurchased. Error Number: 6003. Error Message: Option was not purchased./
+      );
+    });
+  });
+
+  describe("Transfer seller", () => {
+    it("Can transfer short side and let new seller close", async () => {
+      const { program, pda, seller, wsol, context, expiry, setPrice, market } =
+        await fixtureBought();
+
+      const newSeller = Keypair.generate();
+      await airdrop(context, newSeller.publicKey, 1 * LAMPORTS_PER_SOL);
+
+      await program.methods
+        .transferSeller()
+        .accounts({
+          seller: seller.publicKey,
+          newSeller: newSeller.publicKey,
+          data: pda,
+        })
+        .signers([seller])
+        .rpc();
+
+      const data = await program.account.coveredCall.fetch(pda);
+      expect(data.seller).toStrictEqual(newSeller.publicKey);
+      expect(data.creator).toStrictEqual(seller.publicKey);
+
+      await warpTo(context, expiry.add(MARK_GRACE_PERIOD));
+      setPrice(3000);
+      await program.methods
+        .mark(expiry)
+        .accounts({ priceUpdate, market })
+        .rpc();
+
+      await expect(
+        program.methods
+          .close()
+          .accounts({
+            mintBase: wsol,
+            data: pda,
+            seller: seller.publicKey,
+            payer: seller.publicKey,
+            tokenProgram: token.TOKEN_PROGRAM_ID,
+          })
+          .signers([seller])
+          .rpc()
+      ).rejects.toThrowError(
+        "AnchorError caused by account: seller. Error Code: ConstraintRaw. Error Number: 2003. Error Message: A raw constraint was violated."
+      );
+
+      await program.methods
+        .close()
+        .accounts({
+          mintBase: wsol,
+          data: pda,
+          seller: newSeller.publicKey,
+          payer: newSeller.publicKey,
+          tokenProgram: token.TOKEN_PROGRAM_ID,
+        })
+        .signers([newSeller])
+        .rpc();
+
+      expect(await context.banksClient.getAccount(pda)).to.equal(null);
+      expect(
+        await getAtaTokenBalance(context.banksClient, wsol, newSeller.publicKey)
+      ).to.equal(BigInt(1000));
+    });
+
+    it("Can reject transfer by anyone but the seller", async () => {
+      const { program, pda, buyer } = await fixtureBought();
+
+      await expect(
+        program.methods
+          .transferSeller()
+          .accounts({
+            seller: buyer.publicKey,
+            newSeller: buyer.publicKey,
+            data: pda,
+          })
+          .signers([buyer])
+          .rpc()
+      ).rejects.toThrowError(
+        "AnchorError caused by account: seller. Error Code: ConstraintRaw. Error Number: 2003. Error Message: A raw constraint was violated."
+      );
+    });
+  });
+
+  describe("Close instruction", () => {
+    it("Can successfully close exercised option by seller", async () => {
+      const { program, pda, buyer, wsol, context, usdc, seller } =
+        await fixtureExercised();
+
+      expect(await getAtaTokenBalance(context.banksClient, wsol, pda)).to.equal(
+        BigInt(875)
+      );
+      expect(
+        await getAtaTokenBalance(context.banksClient, wsol, seller.publicKey)
+      ).to.equal(BigInt(10));
+
+      await program.methods
+        .close()
+        .accounts({
+          mintBase: wsol,
+          data: pda,
+          seller: seller.publicKey,
+          tokenProgram: token.TOKEN_PROGRAM_ID,
+        })
+        .signers([seller])
+        .rpc();
+
+      // Expect accounts to be close
+      expect(await context.banksClient.getAccount(pda)).to.equal(null);
+      expect(
+        await context.banksClient.getAccount(
+          token.getAssociatedTokenAddressSync(wsol, pda, true)
+        )
+      ).to.equal(null);
+      expect(
+        await context.banksClient.getAccount(
+          token.getAssociatedTokenAddressSync(usdc, pda, true)
+        )
+      ).to.equal(null);
+
+      expect(await getAtaTokenBalance(context.banksClient, wsol, pda)).to.equal(
+        BigInt(0)
+      );
+      expect(
+        await getAtaTokenBalance(context.banksClient, wsol, seller.publicKey)
+      ).to.equal(BigInt(885));
+    });
+
+    it("Can successfully close exercised option by anyone", async () => {
+      const { program, pda, buyer, wsol, context, usdc, seller, payer } =
+        await fixtureExercised();
+
+      const keeper = Keypair.generate();
+      await airdrop(context, keeper.publicKey, 1 * LAMPORTS_PER_SOL);
+
+      expect(await getAtaTokenBalance(context.banksClient, wsol, pda)).to.equal(
+        BigInt(875)
+      );
+      expect(
+        await getAtaTokenBalance(context.banksClient, wsol, seller.publicKey)
+      ).to.equal(BigInt(10));
+
+      await fundAtaAccount(context.banksClient, usdc, seller, 0);
+
+      await program.methods
+        .close()
+        .accounts({
+          mintBase: wsol,
+          data: pda,
+          seller: seller.publicKey,
+          payer: keeper.publicKey,
+          tokenProgram: token.TOKEN_PROGRAM_ID,
+        })
+        .signers([keeper])
+        .rpc();
+
+      expect(await getAtaTokenBalance(context.banksClient, wsol, pda)).to.equal(
+        BigInt(0)
+      );
+      expect(
+        await getAtaTokenBalance(context.banksClient, wsol, seller.publicKey)
+      ).to.equal(BigInt(885));
+    });
+
+    it("Can successfully close unbought option by anyone", async () => {
+      const { program, pda, buyer, wsol, context, usdc, seller } =
+        await fixtureInitialized();
+
+      const keeper = Keypair.generate();
+      await airdrop(context, keeper.publicKey, 1 * LAMPORTS_PER_SOL);
+
+      expect(await getAtaTokenBalance(context.banksClient, wsol, pda)).to.equal(
+        BigInt(1000)
+      );
+      expect(
+        await getAtaTokenBalance(context.banksClient, wsol, seller.publicKey)
+      ).to.equal(BigInt(0));
+
+      await program.methods
+        .close()
+        .accounts({
+          mintBase: wsol,
+          data: pda,
+          seller: seller.publicKey,
+          payer: keeper.publicKey,
+          expiry: null,
+          tokenProgram: token.TOKEN_PROGRAM_ID,
+        })
+        .signers([keeper])
+        .rpc();
+
+      expect(await getAtaTokenBalance(context.banksClient, wsol, pda)).to.equal(
+        BigInt(0)
+      );
+      expect(
+        await getAtaTokenBalance(context.banksClient, wsol, seller.publicKey)
+      ).to.equal(BigInt(1000));
+    });
+
+    it("Can reject closing on a mark that can still change", async () => {
+      const { program, pda, wsol, context, seller, expiry, setPrice, market } =
+        await fixtureBought();
+
+      // An out of the money print the buyer can still replace until the grace
+      // period ends
+      setPrice(3000);
+      await program.methods
+        .mark(expiry)
+        .accounts({ priceUpdate, market })
+        .rpc();
+
+      await warpTo(context, expiry.add(new anchor.BN(100)));
+      await expect(
+        program.methods
+          .close()
+          .accounts({
+            mintBase: wsol,
+            data: pda,
+            seller: seller.publicKey,
+            payer: seller.publicKey,
+            tokenProgram: token.TOKEN_PROGRAM_ID,
+          })
+          .signers([seller])
+          .rpc()
+      ).rejects.toThrowError(
+        /AnchorError thrown in programs\/solana-options\/src\/instructions\/close.rs:\d+. Error Code: OptionCannotBeClosedYet. Error Number: 6004. Error Message: Option cannot be closed Yet./
+      );
+    });
+
+    it("Can successfully close unexercised option after expiry", async () => {
+      const {
+        program,
+        pda,
+        wsol,
+        context,
+        seller,
+        expiry,
+        buyer,
+        setPrice,
+        market,
+      } = await fixtureBought();
+
+      await warpTo(context, expiry.add(MARK_GRACE_PERIOD));
+
+      expect(await getAtaTokenBalance(context.banksClient, wsol, pda)).to.equal(
+        BigInt(1000)
+      );
+      expect(
+        await getAtaTokenBalance(context.banksClient, wsol, seller.publicKey)
+      ).to.equal(BigInt(10));
+
+      setPrice(3000);
+      await program.methods
+        .mark(expiry)
+        .accounts({ priceUpdate, market })
+        .rpc();
+
+      await program.methods
+        .close()
+        .accounts({
+          mintBase: wsol,
+          data: pda,
+          seller: seller.publicKey,
+          payer: seller.publicKey,
+          tokenProgram: token.TOKEN_PROGRAM_ID,
+        })
+        .signers([seller])
+        .rpc();
+
+      // Expect accounts to be close
+      expect(await context.banksClient.getAccount(pda)).to.equal(null);
+      expect(
+        await context.banksClient.getAccount(
+          token.getAssociatedTokenAddressSync(wsol, pda, true)
+        )
+      ).to.equal(null);
+
+      expect(await getAtaTokenBalance(context.banksClient, wsol, pda)).to.equal(
+        BigInt(0)
+      );
+      expect(
+        await getAtaTokenBalance(context.banksClient, wsol, seller.publicKey)
+      ).to.equal(BigInt(1010));
+    });
+
+    it("Can reject closing bought option before expiry", async () => {
+      const {
+        program,
+        pda,
+        wsol,
+        context,
+        usdc,
+        seller,
+        buyer,
+        expiry,
+        setPrice,
+      } = await fixtureBought();
+
+      await expect(
+        program.methods
+          .close()
+          .accounts({
+            data: pda,
+            mintBase: wsol,
+            payer: seller.publicKey,
+            seller: seller.publicKey,
+            expiry: null,
+            tokenProgram: token.TOKEN_PROGRAM_ID,
+          })
+          .signers([seller])
+          .rpc()
+      ).rejects.toThrowError(
+        /AnchorError thrown in programs\/solana-options\/src\/instructions\/close.rs:\d+. Error Code: OptionCannotBeClosedYet. Error Number: 6004. Error Message: Option cannot be closed Yet./
+      );
+    });
+  });
+
+  describe("Cash secured put", () => {
+    it("Can initialize put with quote collateral", async () => {
+      const { program, pda, context, usdc, wsol, seller, buyer } =
+        await fixturePutInitialized();
+
+      expect(await program.account.cashSecuredPut.fetch(pda)).toStrictEqual({
+        amountBase: expect.toBeBN(new BN(1000)),
+        amountPremium: null,
+        amountPremiumAsk: expect.toBeBN(new BN(10)),
+        amountQuote: expect.toBeBN(new BN(3500)),
+        bump: expect.any(Number),
+        creator: seller.publicKey,
+        decimalsBase: 9,
+        decimalsQuote: 6,
+        feedId: SOL_FEED_ID,
+        buyer: buyer.publicKey,
+        isExercised: false,
+        mintBase: wsol,
+        mintPremium: usdc,
+        mintQuote: usdc,
+        nonce: expect.toBeBN(new BN(0)),
+        seller: seller.publicKey,
+        timestampCreated: expect.any(BN),
+        timestampExpiry: expect.any(BN),
+      });
+
+      expect(
+        await getAtaTokenBalance(context.banksClient, usdc, seller.publicKey)
+      ).to.equal(BigInt(0));
+      expect(await getAtaTokenBalance(context.banksClient, usdc, pda)).to.equal(
+        BigInt(3500)
+      );
+    });
+
+    it("Can exercise put in the money", async () => {
+      const {
+        program,
+        pda,
+        buyer,
+        wsol,
+        usdc,
+        context,
+        setPrice,
+        expiry,
+        market,
+      } = await fixturePutBought();
+
+      setPrice(3000);
+      await program.methods
+        .mark(expiry)
+        .accounts({ priceUpdate, market })
+        .rpc();
+      await warpTo(context, expiry.add(MARK_GRACE_PERIOD));
+
+      await program.methods
+        .exercisePut()
+        .accounts({
+          mintBase: wsol,
+          mintQuote: usdc,
+          data: pda,
+          buyer: buyer.publicKey,
+          tokenProgram: token.TOKEN_PROGRAM_ID,
+        })
+        .signers([buyer])
+        .rpc();
+
+      // 3500 * (3500 - 3000) / 3500
+      expect(
+        await getAtaTokenBalance(context.banksClient, usdc, buyer.publicKey)
+      ).to.equal(BigInt(990 + 500));
+      expect(await getAtaTokenBalance(context.banksClient, usdc, pda)).to.equal(
+        BigInt(3500 - 500)
+      );
+    });
+
+    it("Can close put out of the money after expiry", async () => {
+      const { program, pda, usdc, context, seller, setPrice, expiry, market } =
+        await fixturePutBought();
+
+      await warpTo(context, expiry.add(MARK_GRACE_PERIOD));
+      setPrice(4000);
+      await program.methods
+        .mark(expiry)
+        .accounts({ priceUpdate, market })
+        .rpc();
+
+      await program.methods
+        .closePut()
+        .accounts({
+          mintQuote: usdc,
+          data: pda,
+          seller: seller.publicKey,
+          payer: seller.publicKey,
+          tokenProgram: token.TOKEN_PROGRAM_ID,
+        })
+        .signers([seller])
+        .rpc();
+
+      expect(await context.banksClient.getAccount(pda)).to.equal(null);
+      expect(
+        await getAtaTokenBalance(context.banksClient, usdc, seller.publicKey)
+      ).to.equal(BigInt(3510));
+    });
+
+    it("Can reject closing bought put before expiry", async () => {
+      const { program, pda, usdc, seller } = await fixturePutBought();
+
+      await expect(
+        program.methods
+          .closePut()
+          .accounts({
+            mintQuote: usdc,
+            data: pda,
+            seller: seller.publicKey,
+            payer: seller.publicKey,
+            expiry: null,
+            tokenProgram: token.TOKEN_PROGRAM_ID,
+          })
+          .signers([seller])
+          .rpc()
+      ).rejects.toThrowError(
+        /AnchorError thrown in programs\/solana-options\/src\/instructions\/close_put.rs:\d+. Error Code: OptionCannotBeClosedYet. Error Number: 6004. Error Message: Option cannot be closed Yet./
+      );
+    });
+  });
+
+  describe("Option series", () => {
+    const fixtureSeriesWritten = async () => {
+      const fixture = await fixtureDeployed();
+      const { program, wsol, usdc, seller } = fixture;
+      const expiry = new anchor.BN(Math.floor(Date.now() / 1000) + 180);
+
+      await program.methods
+        .initializeSeries(
+          new anchor.BN(1000),
+          new anchor.BN(3500),
+          expiry,
+          { spot: {} }
+        )
+        .accounts({
+          mintBase: wsol,
+          mintQuote: usdc,
+          tokenProgram: token.TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+      const { series, mintOption, mintWriter } = getSeriesPda({
+        amountBase: 1000n,
+        amountQuote: 3500n,
+        expiry: BigInt(expiry.toString()),
+        feedId: SOL_FEED_ID,
+        mintBase: wsol,
+        mintQuote: usdc,
+        programId: program.programId,
+      });
+
+      await program.methods
+        .write(new anchor.BN(1000))
+        .accounts({
+          seller: seller.publicKey,
+          series,
+          mintBase: wsol,
+          mintOption,
+          mintWriter,
+          tokenProgram: token.TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+      return { ...fixture, expiry, series, mintOption, mintWriter };
+    };
+
+    it("Can write option and writer tokens 1:1 with collateral", async () => {
+      const { program, context, series, seller, wsol, mintOption, mintWriter } =
+        await fixtureSeriesWritten();
+
+      expect(
+        await getAtaTokenBalance(
+          context.banksClient,
+          mintOption,
+          seller.publicKey
+        )
+      ).to.equal(BigInt(1000));
+      expect(
+        await getAtaTokenBalance(
+          context.banksClient,
+          mintWriter,
+          seller.publicKey
+        )
+      ).to.equal(BigInt(1000));
+      expect(
+        await getAtaTokenBalance(context.banksClient, wsol, series)
+      ).to.equal(BigInt(1000));
+      expect(
+        (await program.account.optionSeries.fetch(series)).mintOption
+      ).toStrictEqual(mintOption);
+    });
+
+    it("Can reject writing below the market minimum", async () => {
+      const { program, seller, wsol, series, mintOption, mintWriter, market } =
+        await fixtureSeriesWritten();
+
+      await program.methods
+        .updateMarket({ ...marketParams, minAmountBase: new BN(501) })
+        .accounts({ market })
+        .rpc();
+
+      await expect(
+        program.methods
+          .write(new anchor.BN(500))
+          .accounts({
+            seller: seller.publicKey,
+            series,
+            mintBase: wsol,
+            mintOption,
+            mintWriter,
+            tokenProgram: token.TOKEN_PROGRAM_ID,
+          })
+          .rpc()
+      ).rejects.toThrowError(/Error Code: AmountTooSmall. Error Number: 6023/);
+    });
+
+    it("Can exercise option tokens and redeem writer tokens", async () => {
+      const {
+        program,
+        context,
+        series,
+        seller,
+        wsol,
+        mintOption,
+        mintWriter,
+        expiry,
+        setPrice,
+        market,
+      } = await fixtureSeriesWritten();
+
+      setPrice(4000);
+      await program.methods
+        .mark(expiry)
+        .accounts({ priceUpdate, market })
+        .rpc();
+      await warpTo(context, expiry.add(MARK_GRACE_PERIOD));
+
+      await program.methods
+        .exerciseSeries(new anchor.BN(1000))
+        .accounts({
+          buyer: seller.publicKey,
+          series,
+          mintBase: wsol,
+          mintOption,
+          tokenProgram: token.TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+      // 1000 * (4000 - 3500) / 4000
+      expect(
+        await getAtaTokenBalance(context.banksClient, wsol, seller.publicKey)
+      ).to.equal(BigInt(125));
+      expect(
+        await getAtaTokenBalance(
+          context.banksClient,
+          mintOption,
+          seller.publicKey
+        )
+      ).to.equal(BigInt(0));
+
+      await program.methods
+        .redeem(new anchor.BN(1000))
+        .accounts({
+          seller: seller.publicKey,
+          series,
+          mintBase: wsol,
+          mintWriter,
+          tokenProgram: token.TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+      expect(
+        await getAtaTokenBalance(context.banksClient, wsol, seller.publicKey)
+      ).to.equal(BigInt(1000));
+      expect(
+        await getAtaTokenBalance(context.banksClient, wsol, series)
+      ).to.equal(BigInt(0));
+    });
+
+    it("Can redeem writer tokens while paused", async () => {
+      const {
+        program,
+        context,
+        series,
+        seller,
+        wsol,
+        mintWriter,
+        expiry,
+        setPrice,
+        market,
+      } = await fixtureSeriesWritten();
+
+      setPrice(3000);
+      await program.methods
+        .mark(expiry)
+        .accounts({ priceUpdate, market })
+        .rpc();
+      await warpTo(context, expiry.add(MARK_GRACE_PERIOD));
+      await program.methods.setPaused(0xffffffff).rpc();
+
+      await program.methods
+        .redeem(new anchor.BN(1000))
+        .accounts({
+          seller: seller.publicKey,
+          series,
+          mintBase: wsol,
+          mintWriter,
+          tokenProgram: token.TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+      // Out of the money, so all of the collateral goes back
+      expect(
+        await getAtaTokenBalance(context.banksClient, wsol, seller.publicKey)
+      ).to.equal(BigInt(1000));
+    });
+
+    it("Can reject exercise before expiry", async () => {
+      const {
+        program,
+        series,
+        seller,
+        wsol,
+        mintOption,
+        expiry,
+        setPrice,
+        market,
+      } = await fixtureSeriesWritten();
+
+      setPrice(4000);
+      await program.methods
+        .mark(expiry)
+        .accounts({ priceUpdate, market })
+        .rpc();
+
+      await expect(
+        program.methods
+          .exerciseSeries(new anchor.BN(1000))
+          .accounts({
+            buyer: seller.publicKey,
+            series,
+            mintBase: wsol,
+            mintOption,
+            tokenProgram: token.TOKEN_PROGRAM_ID,
+          })
+          .rpc()
+      ).rejects.toThrowError(
+        /AnchorError thrown in programs\/solana-options\/src\/instructions\/exercise_series.rs:\d+. Error Code: OptionNotExpired. Error Number: 6006. Error Message: Option has not expired./
+      );
+    });
+
+    it("Can reject exercise and redeem while the mark can change", async () => {
+      const {
+        program,
+        context,
+        series,
+        seller,
+        wsol,
+        mintOption,
+        mintWriter,
+        expiry,
+        setPrice,
+        market,
+      } = await fixtureSeriesWritten();
+
+      setPrice(4000);
+      await program.methods
+        .mark(expiry)
+        .accounts({ priceUpdate, market })
+        .rpc();
+      await warpTo(context, expiry.add(new anchor.BN(100)));
+
+      await expect(
+        program.methods
+          .exerciseSeries(new anchor.BN(1000))
+          .accounts({
+            buyer: seller.publicKey,
+            series,
+            mintBase: wsol,
+            mintOption,
+            tokenProgram: token.TOKEN_PROGRAM_ID,
+          })
+          .rpc()
+      ).rejects.toThrowError(
+        /AnchorError thrown in programs\/solana-options\/src\/instructions\/exercise_series.rs:\d+. Error Code: MarkNotFinal. Error Number: 6025. Error Message: Expiry mark can still change./
+      );
+      await expect(
+        program.methods
+          .redeem(new anchor.BN(1000))
+          .accounts({
+            seller: seller.publicKey,
+            series,
+            mintBase: wsol,
+            mintWriter,
+            tokenProgram: token.TOKEN_PROGRAM_ID,
+          })
+          .rpc()
+      ).rejects.toThrowError(
+        /AnchorError thrown in programs\/solana-options\/src\/instructions\/redeem.rs:\d+. Error Code: MarkNotFinal. Error Number: 6025. Error Message: Expiry mark can still change./
+      );
+    });
+
+    it("Can reject exercising zero option tokens", async () => {
+      const { program, series, seller, wsol, mintOption } =
+        await fixtureSeriesWritten();
+
+      await expect(
+        program.methods
+          .exerciseSeries(new anchor.BN(0))
+          .accounts({
+            buyer: seller.publicKey,
+            series,
+            mintBase: wsol,
+            mintOption,
+            tokenProgram: token.TOKEN_PROGRAM_ID,
+          })
+          .rpc()
+      ).rejects.toThrowError(
+        /AnchorError thrown in programs\/solana-options\/src\/instructions\/exercise_series.rs:\d+. Error Code: AmountTooSmall. Error Number: 6023. Error Message: Amount is below the market minimum./
+      );
+    });
+  });
+
+  it("has correct price update account", async () => {
+    const { provider } = await fixtureDeployed();
+
+    const SOL_PRICE_FEED_ID =
+      "0xef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d";
+
+    const pyth = new PythSolanaReceiver(provider);
     expect(pyth.getPriceFeedAccountAddress(0, SOL_PRICE_FEED_ID)).to.deep.equal(
       priceUpdate
     );
@@ -1000,7 +3894,8 @@ describe("solana-options", { timeout: 100_000 }, () => {
 
   describe("Can set mark price", () => {
     it("Can reject if mark price is no close enough to expiry", async () => {
-      const { program, context, provider, setPrice } = await fixtureDeployed();
+      const { program, context, provider, setPrice, market } =
+        await fixtureDeployed();
 
       const expiry = new Date();
       const publishTime = new Date(expiry.getTime() - 30 * 60 * 1000 - 1);
@@ -1009,7 +3904,7 @@ describe("solana-options", { timeout: 100_000 }, () => {
       await expect(
         program.methods
           .mark(new anchor.BN(Math.floor(expiry.getTime() / 1000)))
-          .accounts({ priceUpdate })
+          .accounts({ priceUpdate, market })
           .rpc()
       ).rejects.toThrowError(
         /AnchorError thrown in programs\/solana-options\/src\/instructions\/mark.rs:\d\d. Error Code: PriceIrrelevant. Error Number: 6007. Error Message: Price not close to expiry./
@@ -1017,7 +3912,7 @@ describe("solana-options", { timeout: 100_000 }, () => {
     });
 
     it("Can set mark price after expiry", async () => {
-      const { seller, program, context, provider, setPrice } =
+      const { seller, program, context, provider, setPrice, market } =
         await fixtureDeployed();
 
       const expiry = new Date();
@@ -1031,15 +3926,20 @@ describe("solana-options", { timeout: 100_000 }, () => {
 
       await program.methods
         .mark(new anchor.BN(Math.floor(expiry.getTime() / 1000)))
-        .accounts({ priceUpdate })
+        .accounts({ priceUpdate, market })
         .rpc();
 
       expect(
         await program.account.expiryData.fetch(
-          getExpiryPda({ expiry, programId: program.programId })
+          getExpiryPda({
+            expiry,
+            feedId: SOL_FEED_ID,
+            programId: program.programId,
+          })
         )
       ).toStrictEqual({
         bump: expect.any(Number),
+        feedId: SOL_FEED_ID,
         conf: expect.toBeBN(new BN(12190053)),
         payer: seller.publicKey,
         price: expect.toBeBN(new BN(13000000000)),
@@ -1047,11 +3947,16 @@ describe("solana-options", { timeout: 100_000 }, () => {
           new BN(Math.floor(publishTime.getTime() / 1000))
         ),
         exponent: -8,
+        priceSum: expect.toBeBN(new BN(13000000000)),
+        sampleCount: 1,
+        sampleBuckets: expect.toBeBN(new BN(1)),
+        averagePrice: expect.toBeBN(new BN(0)),
+        isFinalized: false,
       });
     });
 
     it("Can set mark price", async () => {
-      const { program, setPrice, seller } = await fixtureDeployed();
+      const { program, setPrice, seller, market } = await fixtureDeployed();
 
       const publishTime = new Date(Date.now() - 1000);
       const expiry = new Date();
@@ -1059,15 +3964,20 @@ describe("solana-options", { timeout: 100_000 }, () => {
 
       await program.methods
         .mark(new anchor.BN(Math.floor(expiry.getTime() / 1000)))
-        .accounts({ priceUpdate })
+        .accounts({ priceUpdate, market })
         .rpc();
 
       expect(
         await program.account.expiryData.fetch(
-          getExpiryPda({ expiry, programId: program.programId })
+          getExpiryPda({
+            expiry,
+            feedId: SOL_FEED_ID,
+            programId: program.programId,
+          })
         )
       ).toStrictEqual({
         bump: expect.any(Number),
+        feedId: SOL_FEED_ID,
         conf: expect.toBeBN(new BN(12190053)),
         payer: seller.publicKey,
         price: expect.toBeBN(new BN(13000000000)),
@@ -1075,11 +3985,69 @@ describe("solana-options", { timeout: 100_000 }, () => {
           new BN(Math.floor(publishTime.getTime() / 1000))
         ),
         exponent: -8,
+        priceSum: expect.toBeBN(new BN(13000000000)),
+        sampleCount: 1,
+        sampleBuckets: expect.toBeBN(new BN(1)),
+        averagePrice: expect.toBeBN(new BN(0)),
+        isFinalized: false,
+      });
+    });
+
+    it("Can reject if price update is for a different feed", async () => {
+      const { program, setPrice, wsol, usdc } = await fixtureDeployed();
+
+      const BTC_FEED_ID = Array.from(
+        Buffer.from(
+          "e62df6c8b4a85fe1a67db44dc12de5db330f7ac66b72dc658afedf0f4a415b43",
+          "hex"
+        )
+      );
+      // Reversed pair so it doesn't clash with the fixture's market
+      await program.methods
+        .initializeMarket(BTC_FEED_ID, marketParams)
+        .accounts({
+          mintBase: usdc,
+          mintQuote: wsol,
+          tokenProgram: token.TOKEN_PROGRAM_ID,
+          tokenProgramQuote: token.TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+      const market = getMarketPda({
+        mintBase: usdc,
+        mintQuote: wsol,
+        programId: program.programId,
       });
+
+      const expiry = new Date();
+      setPrice(130, new Date(expiry.getTime() - 1000));
+
+      await expect(
+        program.methods
+          .mark(new anchor.BN(Math.floor(expiry.getTime() / 1000)))
+          .accounts({ priceUpdate, market })
+          .rpc()
+      ).rejects.toThrowError(/MismatchedFeedId/);
+    });
+
+    it("Can reject if confidence interval is too wide", async () => {
+      const { program, setPrice, market } = await fixtureDeployed();
+
+      const expiry = new Date();
+      // Fixture confidence of 0.12 is over 1% of a price of 10
+      setPrice(10, new Date(expiry.getTime() - 1000));
+
+      await expect(
+        program.methods
+          .mark(new anchor.BN(Math.floor(expiry.getTime() / 1000)))
+          .accounts({ priceUpdate, market })
+          .rpc()
+      ).rejects.toThrowError(
+        /AnchorError thrown in programs\/solana-options\/src\/instructions\/mark.rs:\d\d. Error Code: ConfidenceTooWide. Error Number: 6014. Error Message: Oracle confidence interval is too wide./
+      );
     });
 
     it("Can reject if price is after expiry", async () => {
-      const { program, setPrice } = await fixtureDeployed();
+      const { program, setPrice, market } = await fixtureDeployed();
 
       const expiry = new Date();
       const publishTime = new Date(expiry.getTime() + 1000);
@@ -1088,7 +4056,7 @@ describe("solana-options", { timeout: 100_000 }, () => {
       await expect(
         program.methods
           .mark(new anchor.BN(Math.floor(expiry.getTime() / 1000)))
-          .accounts({ priceUpdate })
+          .accounts({ priceUpdate, market })
           .rpc()
       ).rejects.toThrowError(
         /AnchorError thrown in programs\/solana-options\/src\/instructions\/mark.rs:\d\d. Error Code: PriceIrrelevant. Error Number: 6007. Error Message: Price not close to expiry./
@@ -1096,14 +4064,15 @@ describe("solana-options", { timeout: 100_000 }, () => {
     });
 
     it("Can reject update if mark price is further away", async () => {
-      const { program, context, provider, setPrice } = await fixtureDeployed();
+      const { program, context, provider, setPrice, market } =
+        await fixtureDeployed();
 
       const expiry = new Date();
 
       setPrice(130, new Date(expiry.getTime() - 1000));
       await program.methods
         .mark(new anchor.BN(Math.floor(expiry.getTime() / 1000)))
-        .accounts({ priceUpdate })
+        .accounts({ priceUpdate, market })
         .rpc();
 
       const publishTime = new Date(expiry.getTime() - 2000);
@@ -1114,22 +4083,22 @@ describe("solana-options", { timeout: 100_000 }, () => {
       await expect(
         program.methods
           .mark(new anchor.BN(Math.floor(expiry.getTime() / 1000)))
-          .accounts({ priceUpdate })
+          .accounts({ priceUpdate, market })
           .rpc()
       ).rejects.toThrowError(
-        /AnchorError thrown in programs\/solana-options\/src\/instructions\/mark.rs:\d\d. Error Code: PriceIrrelevant. Error Number: 6007. Error Message: Price not close to expiry./
+        /AnchorError thrown in programs\/solana-options\/src\/instructions\/mark.rs:\d+. Error Code: PriceIrrelevant. Error Number: 6007. Error Message: Price not close to expiry./
       );
     });
 
     it("Can update if mark price is closer", async () => {
-      const { program, setPrice, seller } = await fixtureDeployed();
+      const { program, setPrice, seller, market } = await fixtureDeployed();
 
       const expiry = new Date();
 
       setPrice(130, new Date(expiry.getTime() - 2000));
       await program.methods
         .mark(new anchor.BN(Math.floor(expiry.getTime() / 1000)))
-        .accounts({ priceUpdate })
+        .accounts({ priceUpdate, market })
         .rpc();
 
       await new Promise((resolve) => setTimeout(resolve, 3));
@@ -1138,15 +4107,20 @@ describe("solana-options", { timeout: 100_000 }, () => {
       setPrice(131, publishTime);
       await program.methods
         .mark(new anchor.BN(Math.floor(expiry.getTime() / 1000)))
-        .accounts({ priceUpdate })
+        .accounts({ priceUpdate, market })
         .rpc();
 
       expect(
         await program.account.expiryData.fetch(
-          getExpiryPda({ expiry, programId: program.programId })
+          getExpiryPda({
+            expiry,
+            feedId: SOL_FEED_ID,
+            programId: program.programId,
+          })
         )
       ).toStrictEqual({
         bump: expect.any(Number),
+        feedId: SOL_FEED_ID,
         conf: expect.toBeBN(new BN(12190053)),
         payer: seller.publicKey,
         price: expect.toBeBN(new BN(13100000000)),
@@ -1154,27 +4128,93 @@ describe("solana-options", { timeout: 100_000 }, () => {
           new BN(Math.floor(publishTime.getTime() / 1000))
         ),
         exponent: -8,
+        // Second print is in the same slot as the first, so not a sample
+        priceSum: expect.toBeBN(new BN(13000000000)),
+        sampleCount: 1,
+        sampleBuckets: expect.toBeBN(new BN(1)),
+        averagePrice: expect.toBeBN(new BN(0)),
+        isFinalized: false,
       });
     });
 
-    it("Can close mark price", async () => {
-      const { program, setPrice, context } = await fixtureDeployed();
+    it("Can close mark price before expiry", async () => {
+      const { program, setPrice, context, market } = await fixtureDeployed();
+
+      const expiry = new Date(Date.now() + 60 * 1000);
+      setPrice(130, new Date());
+
+      await program.methods
+        .mark(new anchor.BN(Math.floor(expiry.getTime() / 1000)))
+        .accounts({ priceUpdate, market })
+        .rpc();
+
+      await program.methods
+        .markClose(
+          new anchor.BN(Math.floor(expiry.getTime() / 1000)),
+          SOL_FEED_ID
+        )
+        .rpc();
+
+      expect(
+        await context.banksClient.getAccount(
+          getExpiryPda({
+            expiry,
+            feedId: SOL_FEED_ID,
+            programId: program.programId,
+          })
+        )
+      ).to.equal(null);
+    });
+
+    it("Can reject close mark price after expiry", async () => {
+      const { program, setPrice, context, market } = await fixtureDeployed();
 
       const expiry = new Date();
       setPrice(130, expiry);
 
       await program.methods
         .mark(new anchor.BN(Math.floor(expiry.getTime() / 1000)))
-        .accounts({ priceUpdate })
+        .accounts({ priceUpdate, market })
         .rpc();
 
+      await warpTo(context, new BN(Math.floor(expiry.getTime() / 1000)));
+      await expect(
+        program.methods
+          .markClose(
+            new anchor.BN(Math.floor(expiry.getTime() / 1000)),
+            SOL_FEED_ID
+          )
+          .rpc()
+      ).rejects.toThrowError(
+        /AnchorError thrown in programs\/solana-options\/src\/instructions\/mark_close.rs:\d\d. Error Code: MarkExpired. Error Number: 6026. Error Message: Expiry mark can't be closed while options may settle on it./
+      );
+    });
+
+    it("Can close mark price after the retention period", async () => {
+      const { program, setPrice, context, market } = await fixtureDeployed();
+
+      const expiry = new Date();
+      setPrice(130, expiry);
+
+      const timestamp = new anchor.BN(Math.floor(expiry.getTime() / 1000));
       await program.methods
-        .markClose(new anchor.BN(Math.floor(expiry.getTime() / 1000)))
+        .mark(timestamp)
+        .accounts({ priceUpdate, market })
         .rpc();
 
+      await warpTo(
+        context,
+        timestamp.add(MARK_GRACE_PERIOD).add(MARK_RETENTION_PERIOD)
+      );
+      await program.methods.markClose(timestamp, SOL_FEED_ID).rpc();
+
       expect(
         await context.banksClient.getAccount(
-          getExpiryPda({ expiry, programId: program.programId })
+          getExpiryPda({
+            expiry,
+            feedId: SOL_FEED_ID,
+            programId: program.programId,
+          })
         )
       ).to.equal(null);
     });